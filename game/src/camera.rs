@@ -1,10 +1,13 @@
-use bevy::app::{App, Plugin, Update};
+use bevy::app::{App, Plugin, PostUpdate, Update};
+use bevy::input::keyboard::KeyCode;
 use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll};
 use bevy::input::ButtonInput;
-use bevy::math::{Rect, Vec3};
-use bevy::prelude::{Camera, Camera2d, MouseButton, Single};
+use bevy::math::{Rect, Vec2, Vec3};
+use bevy::prelude::{Camera, Camera2d, GlobalTransform, IntoSystemConfigs, MouseButton, Single, TransformSystem, Without};
 use bevy::prelude::{Component, OrthographicProjection, Query, Res, Transform, With};
+use bevy::time::Time;
 use bevy::window::{PrimaryWindow, Window};
+use bevy_egui::EguiContexts;
 
 pub trait CameraExt {
     fn has_cursor(&self, window: &Window) -> bool;
@@ -18,13 +21,25 @@ impl CameraExt for Camera {
     }
 }
 
+const MIN_SCALE: f32 = 0.001;
+const MAX_SCALE: f32 = 0.1;
+const KEY_PAN_SPEED: f32 = 600.;
+const KEY_ZOOM_SPEED: f32 = 2.;
+const FOLLOW_SPEED: f32 = 8.;
+
 #[derive(Component, Debug, Default)]
 #[require(Transform, Camera2d)]
 pub struct ControllableCamera2d {
     panning: bool,
     pub bounds: Rect,
+    pub follow: bool,
+    pub fit: bool,
 }
 
+/// Marks the entity a [`ControllableCamera2d`] keeps in view while following.
+#[derive(Component, Debug, Default)]
+pub struct CameraTarget;
+
 impl ControllableCamera2d {
     pub fn new(bounds: Rect) -> Self {
         Self { bounds, ..Default::default() }
@@ -35,7 +50,8 @@ pub(super) struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, controls);
+        app.add_systems(Update, controls)
+            .add_systems(PostUpdate, track.before(TransformSystem::TransformPropagate));
     }
 }
 
@@ -44,23 +60,77 @@ fn controls(
     motion: Res<AccumulatedMouseMotion>,
     scroll: Res<AccumulatedMouseScroll>,
     mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
     window: Single<&Window, With<PrimaryWindow>>,
+    mut contexts: EguiContexts,
 ) {
+    let typing = contexts.try_ctx_mut().is_some_and(|ctx| ctx.wants_keyboard_input());
+    let over_ui = contexts.try_ctx_mut().is_some_and(|ctx| ctx.is_pointer_over_area());
     for (mut transform, camera, mut controls, mut projection) in &mut cameras {
-        if mouse.pressed(MouseButton::Left) && (controls.panning || mouse.just_pressed(MouseButton::Left) && camera.has_cursor(&window)) {
+        if mouse.pressed(MouseButton::Left) && (controls.panning || mouse.just_pressed(MouseButton::Left) && camera.has_cursor(&window) && !over_ui) {
             controls.panning = true;
+            if motion.delta != Vec2::ZERO {
+                controls.follow = false;
+            }
             transform.translation.x -= motion.delta.x * projection.scale;
             transform.translation.y += motion.delta.y * projection.scale;
         } else {
             controls.panning = false;
         }
-        if scroll.delta.y != 0. && camera.has_cursor(&window) {
-            let scale = (projection.scale * (1. + -scroll.delta.y.clamp(-3., 3.)/4.)).clamp(0.001, 0.1);
+        if !typing {
+            let pan = [
+                (&[KeyCode::ArrowLeft, KeyCode::KeyA], Vec2::NEG_X),
+                (&[KeyCode::ArrowRight, KeyCode::KeyD], Vec2::X),
+                (&[KeyCode::ArrowUp, KeyCode::KeyW], Vec2::Y),
+                (&[KeyCode::ArrowDown, KeyCode::KeyS], Vec2::NEG_Y),
+            ].into_iter()
+                .filter(|(codes, _)| keys.any_pressed(codes.iter().copied()))
+                .map(|(_, direction)| direction)
+                .sum::<Vec2>();
+            if pan != Vec2::ZERO {
+                controls.follow = false;
+                transform.translation += (pan * KEY_PAN_SPEED * projection.scale * time.delta_secs()).extend(0.);
+            }
+            if keys.any_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+                projection.scale = (projection.scale * (1. - KEY_ZOOM_SPEED * time.delta_secs())).clamp(MIN_SCALE, MAX_SCALE);
+            }
+            if keys.any_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+                projection.scale = (projection.scale * (1. + KEY_ZOOM_SPEED * time.delta_secs())).clamp(MIN_SCALE, MAX_SCALE);
+            }
+            if keys.just_pressed(KeyCode::KeyF) {
+                controls.fit = true;
+            }
+            if keys.just_pressed(KeyCode::KeyC) {
+                controls.follow = !controls.follow;
+            }
+        }
+        if scroll.delta.y != 0. && camera.has_cursor(&window) && !over_ui {
+            let scale = (projection.scale * (1. + -scroll.delta.y.clamp(-3., 3.)/4.)).clamp(MIN_SCALE, MAX_SCALE);
             let factor = scale / projection.scale;
             projection.scale = scale;
             let cursor = window.cursor_position().unwrap_or_default() - camera.logical_viewport_rect().unwrap_or_default().center();
             transform.translation += (cursor - cursor * factor).extend(0.) * projection.scale/factor * Vec3::new(1., -1., 1.);
         }
+    }
+}
+
+fn track(
+    mut cameras: Query<(&mut Transform, &Camera, &mut ControllableCamera2d, &mut OrthographicProjection)>,
+    targets: Query<&GlobalTransform, (With<CameraTarget>, Without<ControllableCamera2d>)>,
+    time: Res<Time>,
+) {
+    for (mut transform, camera, mut controls, mut projection) in &mut cameras {
+        if controls.fit && let Some(viewport) = camera.logical_viewport_size() {
+            let size = controls.bounds.size() / viewport.max(Vec2::ONE);
+            projection.scale = size.max_element().clamp(MIN_SCALE, MAX_SCALE);
+            transform.translation = controls.bounds.center().extend(transform.translation.z);
+            controls.fit = false;
+        }
+        if controls.follow && let Some(target) = targets.iter().next() {
+            let t = 1. - (-FOLLOW_SPEED * time.delta_secs()).exp();
+            transform.translation = transform.translation.lerp(target.translation().with_z(transform.translation.z), t);
+        }
         transform.translation.x = transform.translation.x.clamp(controls.bounds.min.x, controls.bounds.max.x);
         transform.translation.y = transform.translation.y.clamp(controls.bounds.min.y, controls.bounds.max.y);
     }
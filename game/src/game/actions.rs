@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use bevy::hierarchy::{BuildChildren, Parent};
+use bevy::math::Vec3;
+use bevy::prelude::{Commands, Entity, Query, ResMut, Single, Transform, With, Without};
+use crate::game::animation::{self, Tween};
+use crate::game::execution::channel::Run;
+use crate::game::execution::run::tick;
+use crate::game::level::{self, Character, Connection, Item, Room, CHARACTER_Z};
+use crate::game::statistics::{self, Statistics};
+
+#[derive(Debug)]
+pub enum ActionError {
+    InvalidConnection { connection: String, valid: Vec<String> },
+    #[cfg_attr(not(feature = "python"), allow(dead_code))]
+    ConnectionLocked { connection: String, key: Option<String> },
+    WrongKey { connection: String, key: String },
+}
+
+impl Display for ActionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidConnection { connection, valid } => {
+                let hint = suggest(connection, valid).map_or_else(String::new, |suggestion| format!(". Did you mean {suggestion:?}?"));
+                let listing = if valid.is_empty() {
+                    "there are no connections from this room".to_string()
+                } else {
+                    format!("valid connections: {}", valid.iter().map(|name| format!("{name:?}")).collect::<Vec<_>>().join(", "))
+                };
+                write!(f, "Invalid connection: {connection:?}{hint} ({listing})")
+            }
+            Self::ConnectionLocked { connection, .. } => write!(f, "Connection is locked: {connection:?}"),
+            Self::WrongKey { connection, key } => write!(f, "The {key} key doesn't fit connection {connection:?}"),
+        }
+    }
+}
+
+impl Error for ActionError {}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut row: Vec<_> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+pub fn suggest<'a>(name: &str, valid: &'a [String]) -> Option<&'a String> {
+    valid.iter()
+        .map(|candidate| (edit_distance(&name.to_lowercase(), &candidate.to_lowercase()), candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.chars().count().div_ceil(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn valid_connections<'a>(room: Entity, connections: impl Iterator<Item = &'a Connection>) -> Vec<String> {
+    connections.filter(|connection| connection.from == room).map(|connection| connection.name.clone()).collect()
+}
+
+pub fn r#where() -> Option<String> {
+    Run::new(|character: Single<&Parent, With<Character>>, rooms: Query<&Room>| {
+        rooms.get(character.get()).ok().and_then(|room| room.name.clone())
+    }).execute()
+}
+
+pub fn r#move(connection: String) -> Result<(), ActionError> {
+    tick();
+    let counted = statistics::counted();
+    let entity = Run::new(move |mut commands: Commands, mut statistics: ResMut<Statistics>, character: Single<(Entity, &Parent), With<Character>>, connections: Query<&Connection, Without<Character>>, rooms: Query<&Room>| {
+        let (entity, parent) = *character;
+        let Connection { to, locked, key, .. } = connections.iter()
+            .find(|Connection { name, from, .. }| *from == parent.get() && *name == *connection)
+            .ok_or_else(|| ActionError::InvalidConnection { connection: connection.clone(), valid: valid_connections(parent.get(), connections.iter()) })?;
+        if *locked {
+            return Err(ActionError::ConnectionLocked { connection: connection.clone(), key: key.clone() });
+        }
+        let path = match (rooms.get(parent.get()), rooms.get(*to)) {
+            (Ok(from), Ok(to)) => {
+                let (start, end) = level::segment(from, to);
+                [from.rect.center(), start, end, to.rect.center()].map(|point| (point - to.rect.center()).extend(CHARACTER_Z)).to_vec()
+            }
+            _ => vec![Vec3::Z * CHARACTER_Z],
+        };
+        let tween = Tween::along(path);
+        commands.entity(entity).set_parent(*to).insert((Transform::from_translation(tween.start()), tween));
+        if counted {
+            statistics.moves += 1;
+        }
+        Ok(entity)
+    }).execute()?;
+    animation::wait(entity);
+    Ok(())
+}
+
+pub fn pickup() -> Option<Item> {
+    tick();
+    let counted = statistics::counted();
+    let picked = Run::new(move |mut commands: Commands, mut statistics: ResMut<Statistics>, room: Single<&Parent, With<Character>>, items: Query<(Entity, &Parent, &Item, &Transform), Without<Character>>| {
+        if let Some((entity, _, item, transform)) = items.iter().find(|(_, parent, _, _)| parent.get() == room.get()) {
+            let tween = Tween::along(vec![transform.translation, Vec3::Z * (CHARACTER_Z + 1.)])
+                .scale(Vec3::ONE, Vec3::splat(0.2))
+                .despawn();
+            commands.entity(entity).remove::<Item>().insert(tween);
+            if counted {
+                statistics.pickups += 1;
+            }
+            Some((entity, item.clone()))
+        } else {
+            None
+        }
+    }).execute();
+    picked.map(|(entity, item)| {
+        animation::wait(entity);
+        item
+    })
+}
+
+pub fn use_key(name: String, connection: String) -> Result<(), ActionError> {
+    tick();
+    let counted = statistics::counted();
+    let entity = Run::new(move |mut commands: Commands, mut statistics: ResMut<Statistics>, character: Single<&Parent, With<Character>>, mut connections: Query<(Entity, &mut Connection, &Transform), Without<Character>>| {
+        let parent = *character;
+        let Some((entity, mut con, transform)) = connections.iter_mut().find(|(_, con, _)| con.from == parent.get() && *con.name == *connection) else {
+            return Err(ActionError::InvalidConnection { connection: connection.clone(), valid: valid_connections(parent.get(), connections.iter().map(|(_, con, _)| con)) });
+        };
+        if con.key.as_ref() != Some(&name) {
+            return Err(ActionError::WrongKey { connection: connection.clone(), key: name.clone() });
+        }
+        con.locked = !con.locked;
+        commands.entity(entity).insert(Tween::along(vec![transform.translation]).scale(Vec3::new(4., 1., 1.), Vec3::ONE));
+        if counted {
+            statistics.keys += 1;
+        }
+        Ok(entity)
+    }).execute()?;
+    animation::wait(entity);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy::math::Vec3;
+use bevy::prelude::{Commands, Component, Entity, Query, Res, Transform, With};
+use bevy::time::Time;
+use crate::game::execution::channel::Run;
+use crate::game::execution::context::TICK_SPEED;
+
+const TICK_FRACTION: f32 = 0.6;
+
+pub(super) struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, animate);
+    }
+}
+
+pub fn duration() -> f32 {
+    TICK_SPEED.as_secs_f32() * TICK_FRACTION
+}
+
+pub fn blend(delta: f32) -> f32 {
+    (delta / duration() * 4.).min(1.)
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Tween {
+    path: Vec<Vec3>,
+    scale: (Vec3, Vec3),
+    elapsed: f32,
+    despawn: bool,
+}
+
+impl Tween {
+    pub fn along(path: Vec<Vec3>) -> Self {
+        Self { path, scale: (Vec3::ONE, Vec3::ONE), elapsed: 0., despawn: false }
+    }
+
+    pub fn scale(mut self, from: Vec3, to: Vec3) -> Self {
+        self.scale = (from, to);
+        self
+    }
+
+    pub fn despawn(mut self) -> Self {
+        self.despawn = true;
+        self
+    }
+
+    pub fn start(&self) -> Vec3 {
+        self.path.first().copied().unwrap_or_default()
+    }
+
+    fn position(&self, t: f32) -> Vec3 {
+        let lengths: Vec<_> = self.path.windows(2).map(|segment| segment[0].distance(segment[1])).collect();
+        let mut remaining = t * lengths.iter().sum::<f32>();
+        for (segment, length) in self.path.windows(2).zip(lengths) {
+            if remaining <= length && length > 0. {
+                return segment[0].lerp(segment[1], remaining / length);
+            }
+            remaining -= length;
+        }
+        self.path.last().copied().unwrap_or_default()
+    }
+}
+
+fn ease(t: f32) -> f32 {
+    t * t * (3. - 2. * t)
+}
+
+fn animate(mut commands: Commands, time: Res<Time>, mut tweens: Query<(Entity, &mut Transform, &mut Tween)>) {
+    for (entity, mut transform, mut tween) in &mut tweens {
+        tween.elapsed += time.delta_secs();
+        let t = ease((tween.elapsed / duration()).min(1.));
+        transform.translation = tween.position(t);
+        transform.scale = tween.scale.0.lerp(tween.scale.1, t);
+        if tween.elapsed >= duration() {
+            if tween.despawn {
+                commands.entity(entity).despawn_recursive();
+            } else {
+                commands.entity(entity).remove::<Tween>();
+            }
+        }
+    }
+}
+
+pub fn wait(entity: Entity) {
+    while Run::new(move |tweens: Query<(), With<Tween>>| tweens.get(entity).is_ok()).execute() {}
+}
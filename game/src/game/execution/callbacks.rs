@@ -0,0 +1,128 @@
+use std::cell::Cell;
+use std::mem;
+use pyo3::{pyclass, pymethods, Bound, Py, PyAny, PyResult, Python};
+use pyo3::types::PyAnyMethods;
+use crate::game::execution::context::ExecutionContext;
+use crate::game::execution::events::GameEvent;
+use crate::game::execution::run::tick;
+use crate::game::python::GameError;
+
+/// The callbacks a program registered, on its context so threads the program started can register them too.
+#[derive(Default)]
+pub struct Callbacks {
+    enter: Vec<(Option<String>, Py<PyAny>)>,
+    pickup: Vec<Py<PyAny>>,
+    tick: Vec<Py<PyAny>>,
+}
+
+impl Callbacks {
+    fn entered(&self, room: Option<&str>) -> impl Iterator<Item = &Py<PyAny>> {
+        self.enter.iter()
+            .filter(move |(filter, _)| filter.is_none() || filter.as_deref() == room)
+            .map(|(_, callback)| callback)
+    }
+
+    pub fn handles(&self, event: &GameEvent) -> bool {
+        match event {
+            GameEvent::Enter(room) => self.entered(room.as_deref()).next().is_some(),
+            GameEvent::Pickup(_) => !self.pickup.is_empty(),
+        }
+    }
+}
+
+thread_local! {
+    static DISPATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn register(py: Python, f: impl FnOnce(&mut Callbacks)) -> PyResult<()> {
+    let context = ExecutionContext::find(py)
+        .filter(|context| !context.is_closed())
+        .ok_or_else(|| GameError::new_err("Callbacks can only be registered while the program is running"))?;
+    f(&mut context.callbacks.lock().unwrap());
+    Ok(())
+}
+
+#[pyclass]
+pub struct OnEnter(String);
+
+#[pymethods]
+impl OnEnter {
+    fn __call__<'py>(&self, callback: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        register(callback.py(), |callbacks| callbacks.enter.push((Some(self.0.clone()), callback.clone().unbind())))?;
+        Ok(callback)
+    }
+}
+
+pub fn on_enter<'py>(py: Python<'py>, room: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    if room.is_callable() {
+        register(py, |callbacks| callbacks.enter.push((None, room.clone().unbind())))?;
+        return Ok(room);
+    }
+    Ok(Bound::new(py, OnEnter(room.extract()?))?.into_any())
+}
+
+pub fn on_pickup(callback: Bound<PyAny>) -> PyResult<Bound<PyAny>> {
+    register(callback.py(), |callbacks| callbacks.pickup.push(callback.clone().unbind()))?;
+    Ok(callback)
+}
+
+pub fn on_tick(callback: Bound<PyAny>) -> PyResult<Bound<PyAny>> {
+    register(callback.py(), |callbacks| callbacks.tick.push(callback.clone().unbind()))?;
+    Ok(callback)
+}
+
+/// Drops the callbacks of the current program, which reference its globals and so its context.
+pub fn reset() {
+    if let Some(context) = ExecutionContext::current() {
+        // Dropped after the lock is released, as dropping them can run Python code
+        let _callbacks = mem::take(&mut *context.callbacks.lock().unwrap());
+    }
+}
+
+pub fn dispatch(py: Python) -> PyResult<()> {
+    if DISPATCHING.replace(true) {
+        return Ok(());
+    }
+    let result = dispatch_events(py);
+    DISPATCHING.set(false);
+    result
+}
+
+fn dispatch_events(py: Python) -> PyResult<()> {
+    let Some(context) = ExecutionContext::current() else { return Ok(()); };
+    while let Some(event) = context.pop_event() {
+        match event {
+            GameEvent::Enter(room) => {
+                let callbacks: Vec<_> = context.callbacks.lock().unwrap().entered(room.as_deref())
+                    .map(|callback| callback.clone_ref(py))
+                    .collect();
+                for callback in callbacks {
+                    callback.call1(py, (room.clone(),))?;
+                }
+            }
+            GameEvent::Pickup(item) => {
+                let callbacks: Vec<_> = context.callbacks.lock().unwrap().pickup.iter().map(|callback| callback.clone_ref(py)).collect();
+                for callback in callbacks {
+                    callback.call1(py, (item.clone(),))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn event_loop(py: Python) -> PyResult<()> {
+    let Some(context) = ExecutionContext::current() else { return Ok(()); };
+    dispatch(py)?;
+    loop {
+        let callbacks: Vec<_> = context.callbacks.lock().unwrap().tick.iter().map(|callback| callback.clone_ref(py)).collect();
+        if callbacks.is_empty() || context.is_cancelled() {
+            return Ok(());
+        }
+        py.allow_threads(tick);
+        for callback in callbacks {
+            callback.call0(py)?;
+        }
+        dispatch(py)?;
+    }
+}
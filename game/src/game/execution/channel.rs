@@ -1,22 +1,17 @@
 use bevy::prelude::IntoSystem;
 use std::sync::{Arc, RwLock};
-use std::sync::{mpsc, Mutex, OnceLock};
+use std::sync::OnceLock;
 use bevy::app::{App, Plugin, Update};
-use bevy::prelude::{Resource, System, World};
-
-static TX: OnceLock<mpsc::Sender<Arc<dyn Runnable + Send + Sync>>> = OnceLock::new();
-
-#[derive(Resource)]
-struct Rx(Arc<Mutex<mpsc::Receiver<Arc<dyn Runnable + Send + Sync>>>>);
+use bevy::prelude::{System, World};
+use crate::game::execution::context::{Execution, ExecutionContext, Processing};
+#[cfg(feature = "python")]
+use crate::game::execution::events;
 
 pub(super) struct ChannelPlugin;
 
 impl Plugin for ChannelPlugin {
     fn build(&self, app: &mut App) {
-        let (tx, rx) = mpsc::channel();
-        TX.set(tx).unwrap();
-        app.insert_resource(Rx(Arc::new(Mutex::new(rx))))
-            .add_systems(Update, tick);
+        app.add_systems(Update, tick);
     }
 }
 
@@ -40,7 +35,7 @@ impl<T: Send + Sync + 'static, S: System<In = (), Out = T>> Run<T, S> {
 
     pub fn execute(self) -> T {
         let arc = Arc::new(self);
-        TX.get().unwrap().send(arc.clone()).unwrap();
+        ExecutionContext::current().expect("Run executed outside of an execution context").send(arc.clone());
         arc.result.read().unwrap().wait();
         let result = arc.result.write().unwrap().take().unwrap();
         result
@@ -50,13 +45,18 @@ impl<T: Send + Sync + 'static, S: System<In = (), Out = T>> Run<T, S> {
 impl<T: Send + Sync + 'static, S: System<In = (), Out = T>> Runnable for Run<T, S> {
     fn run(&self, world: &mut World) {
         let system = world.register_system(self.system.write().unwrap().take().unwrap());
-        self.result.read().unwrap().set(world.run_system(system).unwrap()).map_err(|_| "Already run").unwrap();
+        let result = world.run_system(system).unwrap();
+        #[cfg(feature = "python")]
+        world.run_system_cached(events::entered).unwrap();
+        self.result.read().unwrap().set(result).map_err(|_| "Already run").unwrap();
     }
 }
 
 pub fn tick(world: &mut World) {
-    let rx = world.get_resource::<Rx>().unwrap().0.clone();
-    for run in rx.lock().unwrap().try_iter() {
-        run.run(world);
+    let contexts: Vec<_> = world.query::<&Execution>().iter(world).map(|Execution(context)| context.clone()).collect();
+    for context in contexts {
+        world.insert_resource(Processing(context.clone()));
+        context.process(world);
+        world.remove_resource::<Processing>();
     }
 }
@@ -0,0 +1,306 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Condvar, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use bevy::prelude::{Component, Resource, World};
+#[cfg(feature = "python")]
+use pyo3::{pyclass, Python};
+#[cfg(feature = "python")]
+use pyo3::types::{PyAnyMethods, PyDict, PyDictMethods};
+use crate::game::execution::channel::Runnable;
+#[cfg(feature = "python")]
+use crate::game::execution::callbacks::Callbacks;
+#[cfg(feature = "python")]
+use crate::game::execution::events::GameEvent;
+#[cfg(feature = "python")]
+use crate::game::execution::importer::Modules;
+
+pub const TICK_SPEED: Duration = Duration::from_millis(500);
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<ExecutionContext>>> = const { RefCell::new(None) };
+}
+
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub enum StepMode {
+    #[default]
+    Action,
+    Into,
+    Over,
+    Out,
+    Cursor(String, usize),
+}
+
+/// One per running program, on the same entity as its [`ProgramTask`](super::run::ProgramTask).
+#[derive(Component, Clone)]
+pub struct Execution(pub Arc<ExecutionContext>);
+
+/// The context whose runnables are currently being processed, so observers can tell which program caused a change.
+#[derive(Resource, Clone)]
+#[cfg_attr(not(feature = "python"), allow(dead_code))]
+pub struct Processing(pub Arc<ExecutionContext>);
+
+/// Stored in the globals of Python programs so game functions called from other threads find their context.
+#[cfg(feature = "python")]
+#[pyclass(frozen)]
+pub struct ContextHandle(pub Arc<ExecutionContext>);
+
+#[cfg(feature = "python")]
+pub const CONTEXT_GLOBAL: &str = "__pythoneer_context__";
+
+struct Entered(Option<Arc<ExecutionContext>>);
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        CURRENT.set(self.0.take());
+    }
+}
+
+pub struct ExecutionContext {
+    tx: mpsc::Sender<Arc<dyn Runnable + Send + Sync>>,
+    rx: Mutex<mpsc::Receiver<Arc<dyn Runnable + Send + Sync>>>,
+    pub stepper: Stepper,
+    next_tick: RwLock<Option<Instant>>,
+    cancelled: AtomicBool,
+    pause_requested: AtomicBool,
+    closed: AtomicBool,
+    repl: bool,
+    #[cfg(feature = "python")]
+    events: Mutex<VecDeque<GameEvent>>,
+    step_mode: Mutex<StepMode>,
+    location: Mutex<Option<(String, usize, usize)>>,
+    depth: AtomicUsize,
+    position: Mutex<Option<(String, usize)>>,
+    hits: Mutex<HashMap<String, HashMap<usize, u32>>>,
+    #[cfg(feature = "python")]
+    pub modules: Mutex<Modules>,
+    #[cfg(feature = "python")]
+    pub callbacks: Mutex<Callbacks>,
+}
+
+impl ExecutionContext {
+    pub fn new() -> Arc<Self> {
+        Self::with_repl(false)
+    }
+
+    /// A context for console input evaluated after the program finished, which doesn't count towards its results.
+    #[cfg_attr(not(feature = "python"), allow(dead_code))]
+    pub fn repl() -> Arc<Self> {
+        Self::with_repl(true)
+    }
+
+    fn with_repl(repl: bool) -> Arc<Self> {
+        let (tx, rx) = mpsc::channel();
+        Arc::new(Self {
+            tx,
+            rx: Mutex::new(rx),
+            stepper: Stepper::new(),
+            next_tick: RwLock::new(Some(Instant::now() + TICK_SPEED)),
+            cancelled: AtomicBool::new(false),
+            pause_requested: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            repl,
+            #[cfg(feature = "python")]
+            events: Mutex::default(),
+            step_mode: Mutex::default(),
+            location: Mutex::new(None),
+            depth: AtomicUsize::new(0),
+            position: Mutex::new(None),
+            hits: Mutex::default(),
+            #[cfg(feature = "python")]
+            modules: Mutex::default(),
+            #[cfg(feature = "python")]
+            callbacks: Mutex::default(),
+        })
+    }
+
+    pub fn current() -> Option<Arc<Self>> {
+        CURRENT.with_borrow(Clone::clone)
+    }
+
+    /// Finds the context of the Python program calling into the game, including from threads the program started.
+    #[cfg(feature = "python")]
+    pub fn find(py: Python) -> Option<Arc<Self>> {
+        Self::current().or_else(|| {
+            let mut frame = py.import("sys").ok()?.call_method1("_getframe", (0,)).ok()?;
+            while !frame.is_none() {
+                if let Ok(Some(handle)) = frame.getattr("f_globals").and_then(|globals| globals.downcast_into::<PyDict>()?.get_item(CONTEXT_GLOBAL))
+                    && let Ok(handle) = handle.downcast_into::<ContextHandle>() {
+                    return Some(handle.get().0.clone());
+                }
+                frame = frame.getattr("f_back").ok()?;
+            }
+            None
+        })
+    }
+
+    pub fn enter<T>(self: &Arc<Self>, f: impl FnOnce() -> T) -> T {
+        let _entered = Entered(CURRENT.replace(Some(self.clone())));
+        f()
+    }
+
+    pub fn send(&self, runnable: Arc<dyn Runnable + Send + Sync>) {
+        self.tx.send(runnable).unwrap();
+    }
+
+    pub fn process(&self, world: &mut World) {
+        for run in self.rx.lock().unwrap().try_iter() {
+            run.run(world);
+        }
+    }
+
+    pub fn wait_for_tick(&self) {
+        if let Some(next_tick) = *self.next_tick.read().unwrap() && next_tick > Instant::now() {
+            thread::sleep_until(next_tick);
+        }
+        self.reset_tick();
+    }
+
+    pub fn reset_tick(&self) {
+        *self.next_tick.write().unwrap() = Some(Instant::now() + TICK_SPEED);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.stepper.wake();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Pauses the program at its next line, even if it doesn't perform any more actions.
+    pub fn request_pause(&self) {
+        self.pause_requested.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_pause_requested(&self) -> bool {
+        self.pause_requested.load(Ordering::Relaxed)
+    }
+
+    pub fn is_repl(&self) -> bool {
+        self.repl
+    }
+
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the program has exited, after which nothing processes its runnables anymore.
+    #[cfg_attr(not(feature = "python"), allow(dead_code))]
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Queues the event for the program's callbacks, if it registered any for it.
+    #[cfg(feature = "python")]
+    pub fn push_event(&self, event: GameEvent) {
+        if self.callbacks.lock().unwrap().handles(&event) {
+            self.events.lock().unwrap().push_back(event);
+        }
+    }
+
+    pub fn step_mode(&self) -> StepMode {
+        self.step_mode.lock().unwrap().clone()
+    }
+
+    pub fn set_step_mode(&self, mode: StepMode) {
+        *self.step_mode.lock().unwrap() = mode;
+    }
+
+    pub fn position(&self) -> Option<(String, usize)> {
+        self.position.lock().unwrap().clone()
+    }
+
+    pub fn hit(&self, file: &str, line: usize) {
+        let mut hits = self.hits.lock().unwrap();
+        if let Some(lines) = hits.get_mut(file) {
+            *lines.entry(line).or_default() += 1;
+        } else {
+            hits.insert(file.to_string(), HashMap::from([(line, 1)]));
+        }
+    }
+
+    pub fn take_hits(&self) -> HashMap<String, HashMap<usize, u32>> {
+        std::mem::take(&mut self.hits.lock().unwrap())
+    }
+
+    pub fn should_pause(&self, file: &str, line: usize, depth: usize) -> bool {
+        match &*self.step_mode.lock().unwrap() {
+            StepMode::Action => false,
+            StepMode::Into => true,
+            StepMode::Over => depth <= self.depth.load(Ordering::Relaxed),
+            StepMode::Out => depth < self.depth.load(Ordering::Relaxed),
+            StepMode::Cursor(cursor_file, cursor_line) => cursor_file == file && *cursor_line == line,
+        }
+    }
+
+    /// Remembers the line the program is on, so pausing between lines can step over and out from it.
+    pub fn trace(&self, file: &str, line: usize, depth: usize) {
+        *self.location.lock().unwrap() = Some((file.to_string(), line, depth));
+    }
+
+    pub fn pause(&self) {
+        self.pause_requested.store(false, Ordering::Relaxed);
+        let location = self.location.lock().unwrap().clone();
+        if let Some((file, line, depth)) = location {
+            self.pause_at(&file, line, depth);
+        } else {
+            self.stepper.wait();
+        }
+    }
+
+    pub fn pause_at(&self, file: &str, line: usize, depth: usize) {
+        self.pause_requested.store(false, Ordering::Relaxed);
+        self.depth.store(depth, Ordering::Relaxed);
+        *self.position.lock().unwrap() = Some((file.to_string(), line));
+        self.stepper.wait();
+        *self.position.lock().unwrap() = None;
+    }
+
+    #[cfg(feature = "python")]
+    pub fn pop_event(&self) -> Option<GameEvent> {
+        self.events.lock().unwrap().pop_front()
+    }
+}
+
+pub struct Stepper {
+    waiting: Mutex<bool>,
+    condvar: Condvar,
+    skip: AtomicBool,
+}
+
+impl Stepper {
+    pub const fn new() -> Self {
+        Stepper {
+            waiting: Mutex::new(false),
+            condvar: Condvar::new(),
+            skip: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        *self.waiting.lock().unwrap()
+    }
+
+    pub fn wake(&self) {
+        self.waiting.set(false).unwrap();
+        self.condvar.notify_all();
+    }
+
+    pub fn skip(&self) {
+        self.skip.store(true, Ordering::Relaxed);
+    }
+
+    pub fn wait(&self) {
+        if self.skip.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        self.waiting.set(true).unwrap();
+        drop(self.condvar.wait_while(self.waiting.lock().unwrap(), |waiting| *waiting).unwrap());
+    }
+}
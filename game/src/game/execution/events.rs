@@ -0,0 +1,44 @@
+use bevy::app::{App, Plugin};
+use bevy::hierarchy::Parent;
+use bevy::prelude::{Changed, OnRemove, Query, Res, Trigger, With};
+use crate::game::execution::context::Processing;
+use crate::game::level::{Character, Item, Room};
+
+pub(super) struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(picked_up);
+    }
+}
+
+/// Changes Python programs can register callbacks for.
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    Enter(Option<String>),
+    Pickup(Item),
+}
+
+pub fn entered(
+    character: Query<&Parent, (With<Character>, Changed<Parent>)>,
+    rooms: Query<&Room>,
+    context: Option<Res<Processing>>,
+) {
+    let Some(Processing(context)) = context.as_deref() else { return; };
+    for parent in &character {
+        if let Ok(room) = rooms.get(parent.get()) {
+            context.push_event(GameEvent::Enter(room.name.clone()));
+        }
+    }
+}
+
+fn picked_up(
+    trigger: Trigger<OnRemove, Item>,
+    items: Query<&Item>,
+    context: Option<Res<Processing>>,
+) {
+    let Some(Processing(context)) = context.as_deref() else { return; };
+    if let Ok(item) = items.get(trigger.entity()) {
+        context.push_event(GameEvent::Pickup(item.clone()));
+    }
+}
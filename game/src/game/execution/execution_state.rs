@@ -32,6 +32,16 @@ impl ExecutionState {
         }
     }
 
+    pub fn can_pause(self) -> bool {
+        match self {
+            Self::Stopped => false,
+            Self::Running => true,
+            Self::Stepping => false,
+            Self::Stopping => false,
+            Self::Finished => false,
+        }
+    }
+
     pub fn can_stop(self) -> bool {
         match self {
             Self::Stopped => false,
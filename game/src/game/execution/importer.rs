@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use pyo3::{pyclass, pymethods, Bound, Py, PyAny, PyResult, Python};
+use pyo3::types::{PyAnyMethods, PyDict, PyDictMethods, PyModule, PyModuleMethods};
+use crate::game::execution::context::{ContextHandle, CONTEXT_GLOBAL};
+use crate::game::execution::run::SourceFile;
+
+/// The files a program can import, and the modules they've been imported as, kept apart from `sys.modules` so concurrent programs don't share them.
+#[derive(Default)]
+pub struct Modules {
+    files: HashMap<String, String>,
+    loaded: HashMap<String, Py<PyModule>>,
+}
+
+/// Replaces `builtins.__import__`, resolving imports from programs through their own files before anything else.
+#[pyclass(frozen)]
+pub struct Importer {
+    original: Py<PyAny>,
+}
+
+#[pymethods]
+impl Importer {
+    #[pyo3(signature = (name, globals = None, locals = None, fromlist = None, level = 0))]
+    fn __call__<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        globals: Option<Bound<'py, PyAny>>,
+        locals: Option<Bound<'py, PyAny>>,
+        fromlist: Option<Bound<'py, PyAny>>,
+        level: i32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let handle = globals.as_ref()
+            .and_then(|globals| globals.get_item(CONTEXT_GLOBAL).ok())
+            .and_then(|handle| handle.downcast_into::<ContextHandle>().ok());
+        if level == 0 && let Some(handle) = handle && let Some(module) = import(&handle, name)? {
+            return Ok(module.into_any());
+        }
+        self.original.bind(py).call1((name, globals, locals, fromlist, level))
+    }
+}
+
+fn import<'py>(handle: &Bound<'py, ContextHandle>, name: &str) -> PyResult<Option<Bound<'py, PyModule>>> {
+    let py = handle.py();
+    let source = {
+        let modules = handle.get().0.modules.lock().unwrap();
+        if let Some(module) = modules.loaded.get(name) {
+            return Ok(Some(module.bind(py).clone()));
+        }
+        let Some(source) = modules.files.get(name) else { return Ok(None); };
+        source.clone()
+    };
+    let module = PyModule::new(py, name)?;
+    module.dict().set_item(CONTEXT_GLOBAL, handle)?;
+    module.dict().set_item("__file__", format!("{name}.py"))?;
+    // Registered before executing, like `sys.modules`, so circular imports get the partially initialised module
+    handle.get().0.modules.lock().unwrap().loaded.insert(name.to_string(), module.clone().unbind());
+    if let Err(e) = execute(py, &source, &format!("{name}.py"), &module.dict()) {
+        handle.get().0.modules.lock().unwrap().loaded.remove(name);
+        return Err(e);
+    }
+    Ok(Some(module))
+}
+
+fn execute(py: Python, source: &str, filename: &str, globals: &Bound<PyDict>) -> PyResult<()> {
+    let lines: Vec<_> = source.split_inclusive('\n').map(|line| if line.ends_with('\n') { line.to_string() } else { format!("{line}\n") }).collect();
+    py.import("linecache")?.getattr("cache")?.set_item(filename, (source.len(), py.None(), lines, filename))?;
+    let builtins = py.import("builtins")?;
+    let code = builtins.call_method1("compile", (source, filename, "exec"))?;
+    builtins.call_method1("exec", (code, globals))?;
+    Ok(())
+}
+
+fn install(py: Python) -> PyResult<()> {
+    let builtins = py.import("builtins")?;
+    let original = builtins.getattr("__import__")?;
+    if !original.is_instance_of::<Importer>() {
+        builtins.setattr("__import__", Importer { original: original.unbind() })?;
+    }
+    Ok(())
+}
+
+pub fn run(py: Python, files: &[SourceFile], globals: &Bound<PyDict>) -> PyResult<()> {
+    let Some((main, modules)) = files.split_first() else { return Ok(()); };
+    install(py)?;
+    if let Some(handle) = globals.get_item(CONTEXT_GLOBAL)? {
+        handle.downcast_into::<ContextHandle>()?.get().0.modules.lock().unwrap().files =
+            modules.iter().map(|file| (file.name.clone(), file.text.clone())).collect();
+    }
+    execute(py, &main.text, &format!("{}.py", main.name), globals)
+}
@@ -2,14 +2,30 @@ use bevy::prelude::Update;
 use bevy::app::{App, Plugin};
 
 pub mod channel;
+pub mod context;
+#[cfg(feature = "python")]
+pub mod events;
 pub mod run;
+#[cfg(feature = "python")]
+pub mod importer;
+#[cfg(feature = "python")]
+pub mod callbacks;
+#[cfg(feature = "python")]
+pub mod tracer;
+#[cfg(feature = "python")]
+pub mod repl;
 pub mod execution_state;
+pub mod syntax;
 
 pub(super) struct ExecutionPlugin;
 
 impl Plugin for ExecutionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(channel::ChannelPlugin)
+        app.init_resource::<context::StepMode>()
+            .add_plugins(channel::ChannelPlugin)
             .add_systems(Update, run::watch);
+        #[cfg(feature = "python")]
+        app.add_plugins(events::EventsPlugin)
+            .add_systems(bevy::prelude::OnEnter(execution_state::ExecutionState::Stopped), repl::close);
     }
 }
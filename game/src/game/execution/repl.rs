@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use bevy::log::error;
+use bevy::prelude::{Commands, In, Query, Res, Resource, With};
+use pyo3::{Bound, Py, PyAny, Python};
+use pyo3::exceptions::PySystemExit;
+use pyo3::types::{PyAnyMethods, PyDict};
+use crate::game::execution::callbacks;
+use crate::game::execution::channel::Run;
+use crate::game::execution::context::{Execution, ExecutionContext};
+use crate::game::execution::run::{spawn_python, ProgramTask};
+
+#[derive(Resource)]
+pub struct Repl {
+    console: Py<PyAny>,
+    more: Arc<AtomicBool>,
+}
+
+impl Repl {
+    pub fn prompt(&self) -> &'static str {
+        if self.more.load(Ordering::Relaxed) { "... " } else { ">>> " }
+    }
+}
+
+pub fn open(py: Python, globals: &Bound<PyDict>) {
+    let Some(context) = ExecutionContext::current() else { return; };
+    if context.is_cancelled() {
+        return;
+    }
+    let console = match py.import("code").and_then(|code| code.getattr("InteractiveConsole")?.call1((globals, "<console>"))) {
+        Ok(console) => console.unbind(),
+        Err(e) => {
+            error!("Failed to start the console: {e}");
+            return;
+        }
+    };
+    let mut console = Some(console);
+    Run::new(move |mut commands: Commands| {
+        if let Some(console) = console.take() {
+            commands.insert_resource(Repl { console, more: Arc::default() });
+        }
+    }).execute();
+}
+
+pub fn close(mut commands: Commands) {
+    commands.remove_resource::<Repl>();
+}
+
+pub fn evaluate(line: In<String>, mut commands: Commands, repl: Option<Res<Repl>>, tasks: Query<(), With<ProgramTask>>) {
+    let Some(repl) = repl else { return; };
+    if !tasks.is_empty() {
+        error!("A program is already running");
+        return;
+    }
+    let line = line.0;
+    let console = Python::with_gil(|py| repl.console.clone_ref(py));
+    let more = repl.more.clone();
+    let context = ExecutionContext::repl();
+    let task = spawn_python(context.clone(), move |py| {
+        match console.call_method1(py, "push", (line,)).and_then(|result| result.extract::<bool>(py)) {
+            Ok(result) => more.store(result, Ordering::Relaxed),
+            Err(e) if !e.is_instance_of::<PySystemExit>(py) => e.display(py),
+            Err(_) => {}
+        }
+        callbacks::reset();
+    });
+    commands.spawn((Execution(context), task));
+}
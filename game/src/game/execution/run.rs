@@ -1,122 +1,228 @@
-use pyo3::Bound;
-use pyo3::types::PyString;
-use bevy::prelude::{error, NextState, ResMut, Resource, State};
-use pyo3::types::PyDict;
+use bevy::prelude::{Component, Entity, NextState, Query, ResMut, State};
 use bevy::prelude::Res;
 use bevy::prelude::{Commands, In};
-use pyo3::types::PyAnyMethods;
-use pyo3::Python;
-use std::ffi::{c_int, c_ulong, CString};
-use std::sync::{Arc, Condvar, Mutex, OnceLock, RwLock};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
-use std::time::{Duration, Instant};
 use bevy::log::debug;
-use bevy::tasks::{AsyncComputeTaskPool, Task};
+#[cfg(feature = "python")]
+use bevy::log::error;
+#[cfg(feature = "python")]
+use pyo3::{Bound, Python};
+#[cfg(feature = "python")]
+use pyo3::types::{PyAnyMethods, PyDict, PyString};
+#[cfg(feature = "python")]
+use pyo3::exceptions::PySystemExit;
+#[cfg(feature = "python")]
 use pyo3::ffi::{PyExc_KeyboardInterrupt, PyObject};
+#[cfg(feature = "python")]
+use std::ffi::{c_int, c_ulong};
+#[cfg(feature = "python")]
+use std::sync::{Arc, OnceLock};
+use std::thread::{self, JoinHandle};
 use crate::game::execution::channel::Run;
+#[cfg(feature = "python")]
+use crate::game::execution::context::{ContextHandle, CONTEXT_GLOBAL};
+use crate::game::execution::context::{Execution, ExecutionContext, StepMode};
+#[cfg(feature = "python")]
+use crate::game::execution::{callbacks, events, importer, repl, tracer};
 use crate::game::execution::execution_state::ExecutionState;
+use crate::game::level::CurrentLevel;
+use crate::game::logging::{InputRequest, Log, Stream};
+#[cfg(feature = "python")]
+use crate::game::requirements;
+use crate::game::starlark::player;
+use crate::game::statistics::Statistics;
+use crate::game::timeline;
 
-const TICK_SPEED: Duration = Duration::from_millis(500);
-static NEXT_TICK: RwLock<Option<Instant>> = RwLock::new(None);
-pub static STEPPER: Stepper = Stepper::new();
-
+#[cfg(feature = "python")]
 extern "C" {
     fn PyThread_get_thread_ident() -> c_ulong;
     fn PyThreadState_SetAsyncExc(id: c_ulong, exc: *mut PyObject) -> c_int;
 }
 
-pub struct Stepper {
-    waiting: Mutex<bool>,
-    condvar: Condvar,
-    skip: AtomicBool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[cfg(feature = "python")]
+    #[default]
+    Python,
+    #[cfg_attr(not(feature = "python"), default)]
+    Starlark,
 }
 
-impl Stepper {
-    pub const fn new() -> Self {
-        Stepper {
-            waiting: Mutex::new(false),
-            condvar: Condvar::new(),
-            skip: AtomicBool::new(false),
+impl Language {
+    pub const ALL: &[Self] = &[
+        #[cfg(feature = "python")]
+        Self::Python,
+        Self::Starlark,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "python")]
+            Self::Python => "Python",
+            Self::Starlark => "Starlark",
         }
     }
 
-    pub fn is_waiting(&self) -> bool {
-        *self.waiting.lock().unwrap()
+    pub const fn extension(self) -> &'static str {
+        match self {
+            #[cfg(feature = "python")]
+            Self::Python => "py",
+            Self::Starlark => "star",
+        }
     }
 
-    pub fn wake(&self) {
-        self.waiting.set(false).unwrap();
-        self.condvar.notify_all();
+    /// Whether the API stubs, which are generated from the Python module, describe this language.
+    pub const fn has_stubs(self) -> bool {
+        match self {
+            #[cfg(feature = "python")]
+            Self::Python => true,
+            Self::Starlark => false,
+        }
     }
+}
 
-    pub fn skip(&self) {
-        self.skip.store(true, Ordering::Relaxed);
-    }
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
 
-    pub fn wait(&self) {
-        if self.skip.swap(false, Ordering::Relaxed) {
-            return;
-        }
-        self.waiting.set(true).unwrap();
-        drop(self.condvar.wait_while(self.waiting.lock().unwrap(), |waiting| *waiting).unwrap());
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { name: name.into(), text: text.into() }
     }
 }
 
-#[derive(Debug, Resource)]
-pub struct PythonTask {
-    task: Task<()>,
-    thread: u64,
+#[derive(Debug, Component)]
+pub struct ProgramTask {
+    task: JoinHandle<()>,
+    #[cfg_attr(not(feature = "python"), allow(dead_code))]
+    thread: Option<u64>,
 }
 
 pub fn run(
-    code: In<String>,
+    program: In<(Language, Vec<SourceFile>)>,
     mut commands: Commands,
-    task: Option<Res<PythonTask>>,
+    execution: Res<State<ExecutionState>>,
+    step_mode: Res<StepMode>,
+    level: Res<CurrentLevel>,
+    mut log: ResMut<Log>,
 ) {
-    assert!(task.is_none());
-    let code = CString::new(&**code).unwrap();
+    let (language, files) = program.0;
+    if !level.0.requirements.is_empty() {
+        match language {
+            #[cfg(feature = "python")]
+            Language::Python => match Python::with_gil(|py| requirements::check(py, &level.0.requirements, &files)) {
+                Ok(violations) => for violation in violations {
+                    log.push(Stream::Stderr, &format!("{violation}\n"));
+                },
+                Err(e) => log.push(Stream::Stderr, &format!("Failed to check the code requirements: {e}\n")),
+            },
+            Language::Starlark => log.push(Stream::Stderr, "This level's code requirements are only checked for Python programs\n"),
+        }
+    }
+    commands.insert_resource(Statistics::start(&files));
+    let context = ExecutionContext::new();
+    context.set_step_mode(step_mode.clone());
+    if *execution == ExecutionState::Stepping && *step_mode == StepMode::Action {
+        context.stepper.skip();
+    }
+    let task = match language {
+        #[cfg(feature = "python")]
+        Language::Python => python(files, context.clone()),
+        Language::Starlark => {
+            let context = context.clone();
+            ProgramTask { task: thread::spawn(move || context.enter(|| player::execute(&files))), thread: None }
+        }
+    };
+    // Runs before the context exists so changes from before the program started aren't reported
+    #[cfg(feature = "python")]
+    commands.run_system_cached(events::entered);
+    commands.spawn((Execution(context), task));
+}
+
+#[cfg(feature = "python")]
+pub(super) fn spawn_python(context: Arc<ExecutionContext>, f: impl FnOnce(Python) + Send + 'static) -> ProgramTask {
     let thread = Arc::new(OnceLock::new());
     let thread2 = thread.clone();
-    let task = AsyncComputeTaskPool::get().spawn(async move {
-        Python::with_gil(move |py| {
-            let pythoneer = py.import("pythoneer").unwrap();
-            let globals = PyDict::new(py);
-            for item in pythoneer.getattr("__all__").unwrap().extract::<Vec<Bound<PyString>>>().unwrap() {
-                globals.set_item(&item, pythoneer.getattr(&item).unwrap()).unwrap();
-            }
+    let task = thread::spawn(move || {
+        context.enter(|| Python::with_gil(move |py| {
             thread2.set(unsafe { PyThread_get_thread_ident() }).unwrap();
-            if let Err(e) = py.run(&code, Some(&globals), None) {
-                error!("{e}");
-                e.display(py);
-            }
-        });
+            f(py);
+        }));
     });
     thread.wait();
-    commands.insert_resource(PythonTask { task, thread: *thread.get().unwrap() });
+    ProgramTask { task, thread: thread.get().copied() }
+}
+
+#[cfg(feature = "python")]
+fn python(files: Vec<SourceFile>, context: Arc<ExecutionContext>) -> ProgramTask {
+    spawn_python(context, move |py| {
+        let pythoneer = py.import("pythoneer").unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("__name__", "__main__").unwrap();
+        if let Some(context) = ExecutionContext::current() {
+            globals.set_item(CONTEXT_GLOBAL, ContextHandle(context)).unwrap();
+        }
+        for item in pythoneer.getattr("__all__").unwrap().extract::<Vec<Bound<PyString>>>().unwrap() {
+            globals.set_item(&item, pythoneer.getattr(&item).unwrap()).unwrap();
+        }
+        let result = tracer::install(py, &files)
+            .and_then(|()| importer::run(py, &files, &globals))
+            .and_then(|()| callbacks::event_loop(py));
+        if let Err(e) = result.and(tracer::uninstall(py))
+            && !e.is_instance_of::<PySystemExit>(py) {
+            error!("{e}");
+            e.display(py);
+        }
+        callbacks::reset();
+        repl::open(py, &globals);
+    })
 }
 
 pub fn watch(
     mut commands: Commands,
-    task: Option<Res<PythonTask>>,
+    tasks: Query<(Entity, &ProgramTask, &Execution)>,
     execution: Res<State<ExecutionState>>,
     mut next_execution: ResMut<NextState<ExecutionState>>,
+    mut statistics: ResMut<Statistics>,
 ) {
-    let Some(task) = task else { return; };
-    if !task.task.is_finished() {
-        if execution.shutdown() {
-            debug!("Sending KeyboardInterrupt");
-            Python::with_gil(|_py| {
-                unsafe {
-                    PyThreadState_SetAsyncExc(task.thread, PyExc_KeyboardInterrupt);
+    if tasks.is_empty() {
+        return;
+    }
+    let mut running = false;
+    for (entity, task, Execution(context)) in &tasks {
+        if !task.task.is_finished() {
+            running = true;
+            if execution.shutdown() {
+                #[cfg(feature = "python")]
+                if let Some(thread) = task.thread {
+                    debug!("Sending KeyboardInterrupt");
+                    Python::with_gil(|_py| {
+                        unsafe {
+                            PyThreadState_SetAsyncExc(thread, PyExc_KeyboardInterrupt);
+                        }
+                    });
+                }
+                context.cancel();
+                commands.remove_resource::<InputRequest>();
+            }
+            continue;
+        }
+        debug!("Program exited");
+        if !context.is_repl() {
+            for (file, hits) in context.take_hits() {
+                let lines = statistics.hits.entry(file).or_default();
+                for (line, count) in hits {
+                    *lines.entry(line).or_default() += count;
                 }
-            });
-            STEPPER.wake();
+            }
         }
+        context.close();
+        commands.entity(entity).despawn();
+    }
+    if running {
         return;
     }
-    debug!("Python exited");
-    commands.remove_resource::<PythonTask>();
     if execution.shutdown() {
         next_execution.set(ExecutionState::Stopped);
     } else {
@@ -125,16 +231,42 @@ pub fn watch(
 }
 
 pub fn tick() {
-    if Run::new(|state: Res<State<ExecutionState>>| **state).execute() == ExecutionState::Stepping {
-        STEPPER.wait();
-    } else {
-        if let Some(next_tick) = *NEXT_TICK.read().unwrap() && next_tick > Instant::now() {
-            thread::sleep_until(next_tick);
-        }
-        reset_tick();
+    let Some(context) = ExecutionContext::current() else { return; };
+    if !context.is_repl() {
+        Run::new(timeline::record).execute();
+        Run::new(|mut statistics: ResMut<Statistics>| statistics.ticks += 1).execute();
+    }
+    let paused = || context.step_mode() == StepMode::Action
+        && Run::new(|state: Res<State<ExecutionState>>| **state).execute() == ExecutionState::Stepping;
+    if !paused() {
+        context.wait_for_tick();
+    }
+    if paused() {
+        context.pause();
     }
 }
 
-pub fn reset_tick() {
-    *NEXT_TICK.write().unwrap() = Some(Instant::now() + TICK_SPEED);
+pub fn line(file: &str, line: usize, depth: usize) {
+    let Some(context) = ExecutionContext::current() else { return; };
+    context.hit(file, line);
+    context.trace(file, line, depth);
+    if context.is_cancelled() {
+        return;
+    }
+    if context.is_pause_requested() {
+        context.pause_at(file, line, depth);
+        return;
+    }
+    if !context.should_pause(file, line, depth) {
+        return;
+    }
+    if matches!(context.step_mode(), StepMode::Cursor(..)) {
+        context.set_step_mode(StepMode::Into);
+    }
+    Run::new(|state: Res<State<ExecutionState>>, mut next_execution: ResMut<NextState<ExecutionState>>| {
+        if *state == ExecutionState::Running {
+            next_execution.set(ExecutionState::Stepping);
+        }
+    }).execute();
+    context.pause_at(file, line, depth);
 }
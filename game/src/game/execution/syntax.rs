@@ -0,0 +1,67 @@
+#[cfg(feature = "python")]
+use pyo3::Python;
+#[cfg(feature = "python")]
+use pyo3::exceptions::PySyntaxError;
+#[cfg(feature = "python")]
+use pyo3::types::PyAnyMethods;
+use starlark::codemap::{FileSpan, ResolvedSpan};
+use starlark::syntax::AstModule;
+use crate::game::execution::run::{Language, SourceFile};
+use crate::game::starlark::player::PLAYER_DIALECT;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub message: String,
+}
+
+pub fn check(language: Language, file: &SourceFile) -> Option<SyntaxError> {
+    let filename = format!("{}.{}", file.name, language.extension());
+    match language {
+        #[cfg(feature = "python")]
+        Language::Python => python(&filename, &file.text),
+        Language::Starlark => starlark(&filename, &file.text),
+    }
+}
+
+#[cfg(feature = "python")]
+fn python(filename: &str, text: &str) -> Option<SyntaxError> {
+    Python::with_gil(|py| {
+        let builtins = py.import("builtins").ok()?;
+        let error = builtins.call_method1("compile", (text, filename, "exec")).err()?;
+        if !error.is_instance_of::<PySyntaxError>(py) {
+            return None;
+        }
+        let value = error.value(py);
+        let get = |name: &str| value.getattr(name).ok()?.extract::<usize>().ok().filter(|value| *value > 0);
+        let line = get("lineno").unwrap_or(1);
+        let column = get("offset").unwrap_or(1) - 1;
+        Some(SyntaxError {
+            line,
+            column,
+            end_line: get("end_lineno").unwrap_or(line),
+            end_column: get("end_offset").map_or(column, |offset| offset - 1),
+            message: value.getattr("msg").and_then(|msg| msg.extract()).unwrap_or_else(|_| error.to_string()),
+        })
+    })
+}
+
+fn starlark(filename: &str, text: &str) -> Option<SyntaxError> {
+    AstModule::parse(filename, text.to_string(), &PLAYER_DIALECT).err().as_ref().map(SyntaxError::from)
+}
+
+impl From<&starlark::Error> for SyntaxError {
+    fn from(error: &starlark::Error) -> Self {
+        let span = error.span().map_or_else(ResolvedSpan::default, FileSpan::resolve_span);
+        Self {
+            line: span.begin.line + 1,
+            column: span.begin.column,
+            end_line: span.end.line + 1,
+            end_column: span.end.column,
+            message: error.without_diagnostic().to_string(),
+        }
+    }
+}
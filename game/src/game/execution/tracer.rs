@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use pyo3::{pyclass, pymethods, Bound, PyAny, PyResult, Python};
+use pyo3::types::PyAnyMethods;
+use crate::game::execution::run::{line, SourceFile};
+
+#[pyclass]
+pub struct Tracer {
+    files: HashSet<String>,
+    depth: AtomicUsize,
+}
+
+#[pymethods]
+impl Tracer {
+    fn __call__<'py>(slf: &Bound<'py, Self>, frame: &Bound<'py, PyAny>, event: &str, _arg: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, Self>>> {
+        let filename: String = frame.getattr("f_code")?.getattr("co_filename")?.extract()?;
+        let tracer = slf.borrow();
+        if !tracer.files.contains(&filename) {
+            return Ok(None);
+        }
+        match event {
+            "call" => {
+                tracer.depth.fetch_add(1, Ordering::Relaxed);
+            }
+            "return" => {
+                tracer.depth.fetch_sub(1, Ordering::Relaxed);
+            }
+            "line" => {
+                let number: usize = frame.getattr("f_lineno")?.extract()?;
+                let depth = tracer.depth.load(Ordering::Relaxed);
+                slf.py().allow_threads(|| line(&filename, number, depth));
+            }
+            _ => {}
+        }
+        Ok(Some(slf.clone()))
+    }
+}
+
+pub fn install(py: Python, files: &[SourceFile]) -> PyResult<()> {
+    let tracer = Bound::new(py, Tracer {
+        files: files.iter().map(|file| format!("{}.py", file.name)).collect(),
+        depth: AtomicUsize::new(0),
+    })?;
+    py.import("sys")?.call_method1("settrace", (tracer,))?;
+    Ok(())
+}
+
+pub fn uninstall(py: Python) -> PyResult<()> {
+    py.import("sys")?.call_method1("settrace", (py.None(),))?;
+    Ok(())
+}
@@ -1,29 +1,34 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::anyhow;
 use bevy::app::{App, Plugin, Update};
-use crate::camera::ControllableCamera2d;
+use crate::camera::{CameraTarget, ControllableCamera2d};
 use crate::game::starlark::DIALECT;
 use crate::geometry::RectExt;
 use bevy::asset::Assets;
-use bevy::color::{Color, Srgba};
-use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt, Parent};
+use bevy::color::{Color, Mix, Srgba};
+use bevy::hierarchy::{BuildChildren, ChildBuild, Children, DespawnRecursiveExt, Parent};
 use bevy::log::debug;
-use bevy::math::{Dir2, Dir3, Rect, Vec3};
-use bevy::prelude::{Commands, Component, Entity, Mesh, Mesh2d, OrthographicProjection, Query, Rectangle, Res, ResMut, Resource, Segment2d, Transform, With};
-use bevy::sprite::{ColorMaterial, MeshMaterial2d};
-use bevy::text::{Text2d, TextColor, TextFont};
-use pyo3::IntoPyObject;
+use bevy::math::{Dir2, Dir3, Rect, Vec2, Vec3};
+use bevy::prelude::{Alpha, Annulus, Bundle, Capsule2d, Circle, Commands, Component, DetectChangesMut, Ellipse, Entity, Has, IntoSystemConfigs, Mesh, Mesh2d, OrthographicProjection, Query, Rectangle, RegularPolygon, Res, ResMut, Resource, Rhombus, Segment2d, Transform, Visibility, With, Without};
+use bevy::sprite::{AlphaMode2d, ColorMaterial, MeshMaterial2d};
+use bevy::text::{JustifyText, Text2d, TextBounds, TextColor, TextFont, TextLayout};
 use starlark::environment::{Globals, Module};
 use starlark::eval::Evaluator;
 use starlark::syntax::AstModule;
 use starlark::values::Value;
+use bevy::time::Time;
+use crate::game::animation;
 use crate::game::logging::Log;
-use crate::game::python::Key;
+use crate::game::timeline::Timeline;
 
 pub(super) struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(CurrentLevel(parse(include_str!("../../levels/default.plvl"))))
-            .add_systems(Update, connection_tick);
+        app.insert_resource(CurrentLevel(parse("default", include_str!("../../levels/default.plvl")).unwrap()))
+            .add_systems(Update, (connection_tick, padlock_tick, (visit, fog).chain()));
     }
 }
 
@@ -36,65 +41,150 @@ pub struct CurrentLevel(pub Level);
 #[derive(Debug, Component)]
 pub struct Character;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Component)]
+pub struct Visited;
+
+pub const CHARACTER_Z: f32 = 100.;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Level {
+    pub id: String,
+    pub path: Option<PathBuf>,
+    pub source: String,
     rooms: Vec<Room>,
     start: usize,
     initial_pan: Option<(i32, i32)>,
-    initial_zoom: f32,
+    initial_zoom: Option<f32>,
+    pub par: Par,
+    pub requirements: Requirements,
+    pub fog: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Par {
+    pub moves: Option<u32>,
+    pub keys: Option<u32>,
+    pub pickups: Option<u32>,
+    pub ticks: Option<u32>,
+    pub lines: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Requirements {
+    pub uses: Vec<String>,
+    pub forbids: Vec<String>,
+    pub max_statements: Option<u32>,
+}
+
+impl Requirements {
+    pub fn is_empty(&self) -> bool {
+        self.uses.is_empty() && self.forbids.is_empty() && self.max_statements.is_none()
+    }
 }
 
-impl<'v> From<&super::starlark::level::Level::Mut<'v>> for Level {
-    fn from(value: &crate::game::starlark::level::Level::Mut<'v>) -> Self {
+impl Level {
+    /// Whether the two levels only differ in their source code.
+    pub fn same_layout(&self, other: &Self) -> bool {
+        Self { source: other.source.clone(), ..self.clone() } == *other
+    }
+
+    fn from(id: &str, source: &str, value: &super::starlark::level::Level::Mut) -> starlark::Result<Self> {
+        let Some(start_value) = *value.start.borrow() else {
+            return Err(starlark::Error::new_other(anyhow!("level.start is not set")));
+        };
         let mut rooms = Vec::with_capacity(value.rooms.borrow().len());
         let mut start = 0;
         for (i, v) in value.rooms.borrow().iter().enumerate() {
-            let room = Room::from(super::starlark::room::Room::from_value(*v).unwrap(), &value.rooms.borrow());
-            if v.ptr_eq(value.start.borrow().unwrap()) {
+            let room = super::starlark::room::Room::from_value(*v).map_err(starlark::Error::new_other)?;
+            let room = Room::from(i, room, &value.rooms.borrow())?;
+            if v.ptr_eq(start_value) {
                 start = i;
             }
             rooms.push(room);
         }
-        Self {
+        for room in &rooms {
+            for connection in &room.connections {
+                if connection.room == room.index {
+                    return Err(starlark::Error::new_other(anyhow!("Connection {:?} leads back into the room it starts in", connection.name)));
+                }
+                if Dir2::new(rooms[connection.room].rect.center() - room.rect.center()).is_err() {
+                    return Err(starlark::Error::new_other(anyhow!("Connection {:?} joins two rooms with the same centre", connection.name)));
+                }
+            }
+        }
+        Ok(Self {
+            id: id.to_string(),
+            path: None,
+            source: source.to_string(),
             rooms,
             start,
             initial_pan: value.initial_pan_x.borrow().zip_with(*value.initial_pan_y.borrow(), |x, y| (x, y)),
-            initial_zoom: 0.1 / value.initial_zoom.borrow().unwrap_or(10) as f32,
-        }
+            initial_zoom: value.initial_zoom.borrow().map(|zoom| 0.1 / zoom as f32),
+            par: Par {
+                moves: *value.par_moves.borrow(),
+                keys: *value.par_keys.borrow(),
+                pickups: *value.par_pickups.borrow(),
+                ticks: *value.par_ticks.borrow(),
+                lines: *value.par_lines.borrow(),
+            },
+            requirements: Requirements {
+                uses: value.uses.borrow().clone(),
+                forbids: value.forbids.borrow().clone(),
+                max_statements: *value.max_statements.borrow(),
+            },
+            fog: *value.fog.borrow(),
+        })
     }
 }
 
-#[derive(Debug, Clone, Component)]
+#[derive(Debug, Clone, PartialEq, Component)]
 pub struct Room {
+    pub index: usize,
     pub rect: Rect,
+    pub name: Option<String>,
+    colour: Option<Color>,
+    label: Option<String>,
     connections: Vec<ConnectionTemplate>,
     item: Option<Item>,
 }
 
 impl Room {
-    fn from<'v>(value: &super::starlark::room::Room::Mut<'v>, others: &[Value<'v>]) -> Self {
-        Self {
+    fn from<'v>(index: usize, value: &super::starlark::room::Room::Mut<'v>, others: &[Value<'v>]) -> starlark::Result<Self> {
+        let connections = value.connections.take().into_iter()
+            .map(|connection| {
+                let name = connection.name.take();
+                let Some((room, _)) = others.iter().enumerate().find(|(_, other)| connection.room.borrow().ptr_eq(**other)) else {
+                    return Err(starlark::Error::new_other(anyhow!("Connection {name:?} leads to a room that is not part of the level")));
+                };
+                Ok(ConnectionTemplate {
+                    name,
+                    room,
+                    locked: *connection.locked.borrow(),
+                    key: connection.key.borrow().clone(),
+                })
+            }).collect::<starlark::Result<_>>()?;
+        let item = value.item.take()
+            .map(|item| super::starlark::level::Key::from_value(item).map(Into::into))
+            .transpose()
+            .map_err(starlark::Error::new_other)?;
+        Ok(Self {
+            index,
             rect: Rect::new(
                 value.pos.borrow().0 as f32 - 0.4,
                 value.pos.borrow().1 as f32 + 0.4,
                 value.pos.borrow().0 as f32 + value.size.borrow().0 as f32 - 0.6,
                 value.pos.borrow().1 as f32 - value.size.borrow().1 as f32 + 0.6,
             ),
-            connections: value.connections.take().into_iter()
-                .map(|connection| ConnectionTemplate {
-                    name: connection.name.take(),
-                    room: others.iter().enumerate()
-                        .find(|(_, other)| connection.room.borrow().ptr_eq(**other))
-                        .unwrap().0,
-                    locked: *connection.locked.borrow(),
-                    key: connection.key.borrow().clone(),
-                }).collect(),
-            item: value.item.take().map(|item| super::starlark::level::Key::from_value(item).unwrap().into()),
-        }
+            name: value.name.take(),
+            colour: value.colour.borrow().as_deref().and_then(colour),
+            label: value.label.take(),
+            connections,
+            item,
+        })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct ConnectionTemplate {
     name: String,
     room: usize,
@@ -111,26 +201,86 @@ pub struct Connection {
     pub key: Option<String>,
 }
 
-#[derive(Debug, Clone, Component, IntoPyObject)]
+#[derive(Debug, Clone, PartialEq, Component)]
+#[cfg_attr(feature = "python", derive(pyo3::IntoPyObject))]
 pub enum Item {
     Key(Key),
 }
 
+/// A key that locks and unlocks connections of the same colour.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+pub struct Key(pub String);
+
+#[derive(Debug, Component)]
+pub struct Padlock(Entity);
+
+#[derive(Debug, Component)]
+pub struct Shackle;
+
+const LOCKED_SHACKLE: Vec3 = Vec3::new(0., 0.045, -0.1);
+const UNLOCKED_SHACKLE: Vec3 = Vec3::new(0.04, 0.08, -0.1);
+
+fn named_colour(name: &str) -> Option<Srgba> {
+    Some(match name {
+        "white" => Srgba::rgb(0.95, 0.95, 0.95),
+        "red" => Srgba::rgb(0.9, 0.2, 0.2),
+        "orange" => Srgba::rgb(0.95, 0.55, 0.1),
+        "yellow" => Srgba::rgb(0.95, 0.85, 0.2),
+        "green" => Srgba::rgb(0.25, 0.75, 0.3),
+        "blue" => Srgba::rgb(0.25, 0.45, 0.95),
+        "pink" => Srgba::rgb(0.95, 0.5, 0.75),
+        "purple" => Srgba::rgb(0.6, 0.3, 0.85),
+        _ => return None,
+    })
+}
+
+pub fn key_colour(name: &str) -> Color {
+    Color::Srgba(named_colour(name).unwrap_or(Srgba::rgb(0.75, 0.5, 0.75)))
+}
+
+pub fn colour(value: &str) -> Option<Color> {
+    named_colour(value).or_else(|| Srgba::hex(value).ok()).map(Color::Srgba)
+}
+
+pub fn key_shape(name: &str) -> Mesh {
+    match name {
+        "white" => Circle::new(0.06).into(),
+        "red" => RegularPolygon::new(0.07, 3).into(),
+        "orange" => Rhombus::new(0.14, 0.1).into(),
+        "yellow" => RegularPolygon::new(0.065, 5).into(),
+        "green" => RegularPolygon::new(0.065, 6).into(),
+        "blue" => Annulus::new(0.035, 0.06).into(),
+        "pink" => Capsule2d::new(0.03, 0.06).into(),
+        "purple" => Ellipse::new(0.04, 0.07).into(),
+        _ => Rectangle::new(0.1, 0.1).into(),
+    }
+}
+
 impl From<&super::starlark::level::Key::Mut> for Item {
     fn from(value: &super::starlark::level::Key::Mut) -> Self {
         Self::Key(Key(value.name.take()))
     }
 }
 
-pub fn parse(code: &str) -> Level {
-    let ast = AstModule::parse("level", code.to_string(), &DIALECT).unwrap();
+fn id(path: &Path) -> String {
+    path.file_stem().map_or_else(|| "level".to_string(), |stem| stem.to_string_lossy().into_owned())
+}
+
+pub fn load(path: &Path) -> starlark::Result<Level> {
+    let source = fs::read_to_string(path).map_err(starlark::Error::new_other)?;
+    Ok(Level { path: Some(path.to_path_buf()), ..parse(&id(path), &source)? })
+}
+
+pub fn parse(id: &str, code: &str) -> starlark::Result<Level> {
+    let ast = AstModule::parse(id, code.to_string(), &DIALECT)?;
     let globals = Globals::standard();
     let module = Module::new();
         let level = module.heap().alloc(super::starlark::level::Level::Mut::default());
     module.set("level", level);
     let mut eval = Evaluator::new(&module);
-    eval.eval_module(ast, &globals).unwrap();
-    super::starlark::level::Level::from_value(level).unwrap().into()
+    eval.eval_module(ast, &globals)?;
+    Level::from(id, code, super::starlark::level::Level::from_value(level).unwrap())
 }
 
 pub fn spawn(
@@ -144,12 +294,14 @@ pub fn spawn(
         .reduce(|a, b| a.union(b))
         .unwrap_or_default()
         .inflate(0.1);
+    let mut camera = ControllableCamera2d::new(bounds);
+    camera.fit = level.0.initial_pan.is_none() && level.0.initial_zoom.is_none();
     commands.spawn((
-        ControllableCamera2d::new(bounds),
+        camera,
         LevelEntity,
         Transform::from_translation(level.0.initial_pan.map_or(bounds.center().extend(0.), |(x, y)| Vec3::new(x as f32, y as f32, 0.))),
         OrthographicProjection {
-            scale: level.0.initial_zoom,
+            scale: level.0.initial_zoom.unwrap_or(0.01),
             ..OrthographicProjection::default_2d()
         },
     ));
@@ -158,25 +310,50 @@ pub fn spawn(
             room.clone(),
             LevelEntity,
             Mesh2d(meshes.add(Rectangle::from_corners(room.rect.min, room.rect.max))),
-            MeshMaterial2d(materials.add(Color::Srgba(Srgba::rgb(0.2, 0.2, 0.2)))),
+            MeshMaterial2d(materials.add(ColorMaterial {
+                color: room.colour.unwrap_or(Color::Srgba(Srgba::rgb(0.2, 0.2, 0.2))),
+                alpha_mode: if level.0.fog { AlphaMode2d::Blend } else { AlphaMode2d::Opaque },
+                ..ColorMaterial::default()
+            })),
             Transform::from_translation(room.rect.center().extend(1.)),
         ));
-        if let Some(item) = &room.item {
+        if level.0.fog {
+            spawn.insert(Visibility::Hidden);
+        }
+        if i == level.0.start {
+            spawn.insert(Visited);
+        }
+        if let Some(name) = &room.name {
             spawn.with_child((
-                item.clone(),
-                LevelEntity,
-                Mesh2d(meshes.add(Rectangle::new(0.1, 0.1))),
-                MeshMaterial2d(materials.add(Color::Srgba(Srgba::rgb(0.75, 0.5, 0.75)))),
-                Transform::from_translation((-room.rect.half_size() + 0.1).extend(50.)),
+                Text2d::new(name.clone()),
+                TextFont::from_font_size(100.),
+                Transform::from_xyz(0., room.rect.half_size().y - 0.1, 5.)
+                    .with_scale(Vec3::splat(0.001)),
+                TextColor(Color::WHITE),
             ));
         }
+        if let Some(label) = &room.label {
+            spawn.with_child((
+                Text2d::new(label.clone()),
+                TextFont::from_font_size(55.),
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextBounds::new_horizontal(room.rect.width() * 900.),
+                Transform::from_xyz(0., room.rect.half_size().y - 0.2, 5.)
+                    .with_scale(Vec3::splat(0.001)),
+                TextColor(Color::Srgba(Srgba::rgb(0.8, 0.8, 0.8))),
+            ));
+        }
+        if let Some(item) = &room.item {
+            spawn.with_child(item_bundle(item, &room, &mut meshes, &mut materials));
+        }
         if i == level.0.start {
             spawn.with_child((
                 Character,
+                CameraTarget,
                 LevelEntity,
                 Mesh2d(meshes.add(Rectangle::new(0.5, 0.5))),
                 MeshMaterial2d(materials.add(Color::Srgba(Srgba::rgb(0.5, 0.5, 0.5)))),
-                Transform::from_xyz(0., 0., 100.),
+                Transform::from_xyz(0., 0., CHARACTER_Z),
             ));
         }
         (spawn.id(), room)
@@ -184,11 +361,9 @@ pub fn spawn(
     for (from, room) in &rooms {
         for connection in &room.connections {
             let (to, to_room) = &rooms[connection.room];
-            let direction = Dir2::new(to_room.rect.center() - room.rect.center()).unwrap();
-            let start = direction * room.rect.radius(direction) + room.rect.center();
-            let end = -direction * to_room.rect.radius(-direction) + to_room.rect.center();
+            let (start, end) = segment(room, to_room);
             let (line, pos) = Segment2d::from_points(start, end);
-            commands.spawn((
+            let connection_entity = commands.spawn((
                 LevelEntity,
                 Connection { name: connection.name.clone(), from: *from, to: *to, locked: connection.locked, key: connection.key.clone() },
                 Mesh2d(meshes.add(Rectangle::new(0.01, line.half_length * 2. - 0.075))),
@@ -200,22 +375,150 @@ pub fn spawn(
                 Transform::from_xyz(0., -line.half_length + 0.025, 1.)
                     .with_scale(Vec3::splat(0.001)),
                 TextColor(Color::WHITE),
-            ));
+            )).id();
+            if connection.locked || connection.key.is_some() {
+                spawn_padlock(&mut commands, connection_entity, connection, start.lerp(end, 0.3), &mut meshes, &mut materials);
+            }
         }
     }
 }
 
+fn spawn_padlock(
+    commands: &mut Commands,
+    connection_entity: Entity,
+    connection: &ConnectionTemplate,
+    position: Vec2,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    let colour = connection.key.as_deref().map_or(Color::Srgba(Srgba::rgb(0.4, 0.4, 0.4)), key_colour);
+    commands.spawn((
+        Padlock(connection_entity),
+        LevelEntity,
+        Mesh2d(meshes.add(Rectangle::new(0.12, 0.09))),
+        MeshMaterial2d(materials.add(colour)),
+        Transform::from_translation(position.extend(20.)),
+    )).with_children(|padlock| {
+        padlock.spawn((
+            Shackle,
+            Mesh2d(meshes.add(Annulus::new(0.028, 0.04))),
+            MeshMaterial2d(materials.add(Color::Srgba(Srgba::rgb(0.7, 0.7, 0.7)))),
+            Transform::from_translation(if connection.locked { LOCKED_SHACKLE } else { UNLOCKED_SHACKLE }),
+        ));
+        if let Some(key) = &connection.key {
+            padlock.spawn((
+                Mesh2d(meshes.add(key_shape(key))),
+                MeshMaterial2d(materials.add(Color::Srgba(Srgba::rgb(0.1, 0.1, 0.1)))),
+                Transform::from_xyz(0., 0., 0.1).with_scale(Vec3::splat(0.4)),
+            ));
+        }
+    });
+}
+
+pub fn segment(from: &Room, to: &Room) -> (Vec2, Vec2) {
+    let direction = Dir2::new(to.rect.center() - from.rect.center()).unwrap();
+    let start = direction * from.rect.radius(direction) + from.rect.center();
+    let end = -direction * to.rect.radius(-direction) + to.rect.center();
+    (start, end)
+}
+
+pub fn item_bundle(
+    item: &Item,
+    room: &Room,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) -> impl Bundle {
+    (
+        item.clone(),
+        LevelEntity,
+        Mesh2d(meshes.add(match item { Item::Key(Key(name)) => key_shape(name) })),
+        MeshMaterial2d(materials.add(match item { Item::Key(Key(name)) => key_colour(name) })),
+        Transform::from_translation((-room.rect.half_size() + 0.1).extend(50.)),
+    )
+}
+
 fn connection_tick(
+    time: Res<Time>,
     mut labels: Query<(&mut TextColor, &Parent)>,
     connection: Query<&Connection>,
 ) {
+    let t = animation::blend(time.delta_secs());
     for (mut colour, parent) in &mut labels {
         if let Ok(connection) = connection.get(parent.get()) {
-            colour.0 = if connection.locked { Color::Srgba(Srgba::rgb(0.4, 0.4, 0.4)) } else { Color::WHITE };
+            let target = if connection.locked { Color::Srgba(Srgba::rgb(0.4, 0.4, 0.4)) } else { Color::WHITE };
+            colour.0 = colour.0.mix(&target, t);
         }
     }
 }
 
+fn padlock_tick(
+    time: Res<Time>,
+    padlocks: Query<(&Padlock, &Children)>,
+    connections: Query<&Connection>,
+    mut shackles: Query<&mut Transform, With<Shackle>>,
+) {
+    let t = animation::blend(time.delta_secs());
+    for (padlock, children) in &padlocks {
+        let Ok(connection) = connections.get(padlock.0) else { continue; };
+        let target = if connection.locked { LOCKED_SHACKLE } else { UNLOCKED_SHACKLE };
+        let mut iter = shackles.iter_many_mut(children);
+        while let Some(mut transform) = iter.fetch_next() {
+            transform.translation = transform.translation.lerp(target, t);
+        }
+    }
+}
+
+fn visit(
+    mut commands: Commands,
+    characters: Query<&Parent, With<Character>>,
+    rooms: Query<(), (With<Room>, Without<Visited>)>,
+) {
+    for room in &characters {
+        if rooms.contains(room.get()) {
+            commands.entity(room.get()).insert(Visited);
+        }
+    }
+}
+
+fn fog(
+    level: Res<CurrentLevel>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rooms: Query<(Entity, Has<Visited>, &MeshMaterial2d<ColorMaterial>, &mut Visibility), With<Room>>,
+    mut connections: Query<(Entity, &Connection, &mut Visibility), Without<Room>>,
+    mut padlocks: Query<(&Padlock, &mut Visibility), (Without<Room>, Without<Connection>)>,
+    mut items: Query<(&Parent, &mut Visibility), (With<Item>, Without<Room>, Without<Connection>, Without<Padlock>)>,
+) {
+    if !level.0.fog {
+        return;
+    }
+    let visited: HashSet<_> = rooms.iter().filter(|(_, visited, ..)| *visited).map(|(entity, ..)| entity).collect();
+    let seen: HashSet<_> = connections.iter()
+        .filter(|(_, connection, _)| visited.contains(&connection.from))
+        .map(|(_, connection, _)| connection.to)
+        .collect();
+    for (entity, is_visited, material, mut visibility) in &mut rooms {
+        let alpha = if is_visited { 1. } else { 0.3 };
+        visibility.set_if_neq(if is_visited || seen.contains(&entity) { Visibility::Inherited } else { Visibility::Hidden });
+        let stale = materials.get(&material.0).is_some_and(|material| (material.color.alpha() - alpha).abs() > f32::EPSILON);
+        if let Some(material) = stale.then(|| materials.get_mut(&material.0)).flatten() {
+            material.color.set_alpha(alpha);
+        }
+    }
+    let mut shown = HashSet::new();
+    for (entity, connection, mut visibility) in &mut connections {
+        if visited.contains(&connection.from) {
+            shown.insert(entity);
+        }
+        visibility.set_if_neq(if shown.contains(&entity) { Visibility::Inherited } else { Visibility::Hidden });
+    }
+    for (padlock, mut visibility) in &mut padlocks {
+        visibility.set_if_neq(if shown.contains(&padlock.0) { Visibility::Inherited } else { Visibility::Hidden });
+    }
+    for (parent, mut visibility) in &mut items {
+        visibility.set_if_neq(if visited.contains(&parent.get()) { Visibility::Inherited } else { Visibility::Hidden });
+    }
+}
+
 pub fn despawn(mut commands: Commands, entities: Query<Entity, With<LevelEntity>>) {
     for entity in &entities {
         commands.entity(entity).despawn_recursive();
@@ -225,9 +528,11 @@ pub fn despawn(mut commands: Commands, entities: Query<Entity, With<LevelEntity>
 pub fn reset(
     mut commands: Commands,
     mut log: ResMut<Log>,
+    mut timeline: ResMut<Timeline>,
 ) {
     debug!("Reset");
     log.0.clear();
+    timeline.clear();
     commands.run_system_cached(despawn);
     commands.run_system_cached(spawn);
 }
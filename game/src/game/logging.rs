@@ -1,17 +1,107 @@
-use bevy::prelude::{ResMut, Resource};
-use pyo3::{pyclass, pymethods};
+use bevy::prelude::Resource;
+#[cfg(feature = "python")]
+use bevy::prelude::{Commands, ResMut};
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pymethods, PyResult, Python};
+#[cfg(feature = "python")]
+use pyo3::types::PyAnyMethods;
+#[cfg(feature = "python")]
 use crate::game::execution::channel::Run;
+#[cfg(feature = "python")]
+use crate::game::execution::context::ExecutionContext;
+#[cfg(feature = "python")]
+use crate::game::python::GameError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub stream: Stream,
+    pub text: String,
+}
 
 #[derive(Resource, Default)]
-pub struct Log(pub String);
+pub struct Log(pub Vec<LogEntry>);
+
+impl Log {
+    pub fn push(&mut self, stream: Stream, text: &str) {
+        if let Some(last) = self.0.last_mut() && last.stream == stream {
+            last.text.push_str(text);
+        } else {
+            self.0.push(LogEntry { stream, text: text.to_string() });
+        }
+    }
+}
 
+pub fn traceback_line(line: &str) -> Option<(&str, usize)> {
+    let line = line.trim_start();
+    let (file, rest) = if let Some(rest) = line.strip_prefix("File \"") {
+        rest.split_once("\", line ")?
+    } else {
+        line.strip_prefix("--> ").or_else(|| line.strip_prefix("* "))?.split_once(':')?
+    };
+    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    Some((file, rest[..digits].parse().ok()?))
+}
+
+/// Exists while a program waits for `input()`, until it takes the line the console fills in.
+#[derive(Resource, Default)]
+pub struct InputRequest(pub Option<String>);
+
+#[cfg(feature = "python")]
 #[pyclass]
-pub struct Logger;
+pub struct Logger(pub Stream);
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl Logger {
+    fn write(&self, py: Python, text: String) -> PyResult<()> {
+        let stream = self.0;
+        if let Some(context) = ExecutionContext::find(py).filter(|context| !context.is_closed()) {
+            py.allow_threads(|| context.enter(|| Run::new(move |mut log: ResMut<Log>| log.push(stream, &text)).execute()));
+        } else {
+            // Output from threads that outlive the program, or aren't part of it, still goes somewhere
+            let name = match stream { Stream::Stdout => "__stdout__", Stream::Stderr => "__stderr__" };
+            py.import("sys")?.getattr(name)?.call_method1("write", (text,))?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::unused_self)]
+    fn flush(&self) {}
+}
+
+#[cfg(feature = "python")]
+#[pyclass]
+pub struct Stdin;
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Stdin {
     #[allow(clippy::unused_self)]
-    fn write(&self, text: String) {
-        Run::new(move |mut log: ResMut<Log>| log.0.push_str(&text)).execute();
+    #[pyo3(signature = (_size = -1))]
+    fn readline(&self, py: Python, _size: i64) -> PyResult<String> {
+        let context = ExecutionContext::find(py)
+            .filter(|context| !context.is_closed())
+            .ok_or_else(|| GameError::new_err("input() can only be used while the program is running"))?;
+        Ok(py.allow_threads(move || context.enter(|| {
+            Run::new(|mut commands: Commands| commands.insert_resource(InputRequest::default())).execute();
+            loop {
+                let line = Run::new(|mut commands: Commands, request: Option<ResMut<InputRequest>>| {
+                    // Removed when the program is stopped
+                    let Some(mut request) = request else { return Some(String::new()); };
+                    let line = request.0.take()?;
+                    commands.remove_resource::<InputRequest>();
+                    Some(line + "\n")
+                }).execute();
+                if let Some(line) = line {
+                    break line;
+                }
+            }
+        })))
     }
 }
@@ -1,10 +1,17 @@
 use bevy::app::{App, Plugin};
 
 pub mod level;
+pub mod actions;
+pub mod animation;
+#[cfg(feature = "python")]
 pub mod python;
 pub mod execution;
 mod starlark;
 pub mod logging;
+pub mod timeline;
+pub mod stubs;
+pub mod statistics;
+pub mod requirements;
 
 pub struct GamePlugin;
 
@@ -12,6 +19,12 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<logging::Log>()
             .add_plugins(level::LevelPlugin)
-            .add_plugins(execution::ExecutionPlugin);
+            .add_plugins(execution::ExecutionPlugin)
+            .add_plugins(timeline::TimelinePlugin)
+            .add_plugins(statistics::StatisticsPlugin)
+            .add_plugins(animation::AnimationPlugin)
+            .init_resource::<stubs::Stubs>();
+        #[cfg(feature = "python")]
+        app.add_systems(bevy::app::Startup, stubs::generate);
     }
 }
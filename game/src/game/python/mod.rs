@@ -1,16 +1,52 @@
 #![pyo3::pymodule(name = "pythoneer", gil_used = false)]
 
-use bevy::hierarchy::{BuildChildren, Parent};
-use bevy::prelude::{Entity, Query, Single, With, Without, Commands};
-use crate::game::execution::channel::Run;
-use pyo3::{pyclass, pyfunction, pymethods, PyResult, Python};
-use pyo3::exceptions::PyValueError;
-use crate::game::execution::run::tick;
-use crate::game::level::{Character, Connection, Item};
+use pyo3::{create_exception, pyfunction, pymethods, Bound, PyAny, PyErr, PyResult, Python};
+use pyo3::exceptions::PyException;
+use pyo3::types::{PyAnyMethods, PyModule, PyModuleMethods};
+use crate::game::actions::{self, suggest, ActionError};
+use crate::game::execution::callbacks;
+use crate::game::execution::context::ExecutionContext;
+use crate::game::level::Item;
 
-#[pyclass]
-#[derive(Debug, Clone)]
-pub struct Key(pub String);
+create_exception!(pythoneer, GameError, PyException, "Base class of all errors raised by the game.");
+create_exception!(pythoneer, InvalidConnection, GameError, "There is no connection with that name in the current room.\n\nHas `connection`, `suggestion` and `valid` attributes.");
+create_exception!(pythoneer, ConnectionLocked, GameError, "The connection is locked and has to be unlocked with a key first.\n\nHas `connection` and `key` attributes.");
+create_exception!(pythoneer, WrongKey, GameError, "The key doesn't fit the connection.\n\nHas `connection` and `key` attributes.");
+
+#[pymodule_init]
+fn init(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add("GameError", module.py().get_type::<GameError>())?;
+    module.add("InvalidConnection", module.py().get_type::<InvalidConnection>())?;
+    module.add("ConnectionLocked", module.py().get_type::<ConnectionLocked>())?;
+    module.add("WrongKey", module.py().get_type::<WrongKey>())?;
+    Ok(())
+}
+
+impl ActionError {
+    fn into_py_err(self, py: Python) -> PyErr {
+        let message = self.to_string();
+        let (err, connection, key) = match self {
+            Self::InvalidConnection { connection, valid } => {
+                let err = InvalidConnection::new_err(message);
+                let value = err.value(py);
+                if let Err(e) = value.setattr("suggestion", suggest(&connection, &valid)).and_then(|()| value.setattr("valid", &valid)) {
+                    return e;
+                }
+                (err, connection, None)
+            }
+            Self::ConnectionLocked { connection, key } => (ConnectionLocked::new_err(message), connection, key),
+            Self::WrongKey { connection, key } => (WrongKey::new_err(message), connection, Some(key)),
+        };
+        let value = err.value(py);
+        if let Err(e) = value.setattr("connection", connection).and_then(|()| value.setattr("key", key)) {
+            return e;
+        }
+        err
+    }
+}
+
+#[pymodule_export]
+use crate::game::level::Key;
 
 #[pymethods]
 impl Key {
@@ -18,48 +54,65 @@ impl Key {
         format!("Key({})", self.0)
     }
  
+    /// Locks or unlocks the connection with the given name leading out of the current room.
+    #[pyo3(text_signature = "($self, connection: str)")]
     fn r#use(&self, py: Python, connection: String) -> PyResult<()> {
         let name = self.0.clone();
-        py.allow_threads(tick);
-        Run::new(move |character: Single<&Parent, With<Character>>, mut connections: Query<&mut Connection, Without<Character>>| {
-            let parent = *character;
-            let mut con = connections.iter_mut()
-                .find(|con| con.from == parent.get() && *con.name == *connection)
-                .ok_or(PyValueError::new_err(format!("Invalid connection: {connection:?}")))?;
-            if con.key.as_ref() != Some(&name) {
-                return Err(PyValueError::new_err(format!("The {name} key doesn't fit connection {connection:?}")))
-            }
-            con.locked = !con.locked;
-            Ok(())
-        }).execute()
+        action(py, || actions::use_key(name, connection))?.map_err(|e| e.into_py_err(py))
+    }
+}
+
+/// Runs a game action in the context of the calling program, which may be on a thread the program started.
+fn action<T: Send>(py: Python, f: impl FnOnce() -> T + Send) -> PyResult<T> {
+    let program_thread = ExecutionContext::current().is_some();
+    let context = ExecutionContext::find(py)
+        .filter(|context| !context.is_closed())
+        .ok_or_else(|| GameError::new_err("Game functions can only be used while the program is running"))?;
+    let result = py.allow_threads(|| context.enter(f));
+    if program_thread {
+        callbacks::dispatch(py)?;
     }
+    Ok(result)
 }
 
+/// Moves the character through the connection with the given name.
 #[pyfunction]
+#[pyo3(text_signature = "(connection: str)")]
 fn r#move(py: Python, connection: String) -> PyResult<()> {
-    py.allow_threads(tick);
-    Run::new(move |mut commands: Commands, character: Single<(Entity, &Parent), With<Character>>, connections: Query<&Connection, Without<Character>>| {
-        let (entity, parent) = *character;
-        let Connection { to, locked, .. } = connections.iter()
-            .find(|Connection { name, from, .. }| *from == parent.get() && *name == *connection)
-            .ok_or(PyValueError::new_err(format!("Invalid connection: {connection:?}")))?;
-        if *locked {
-            return Err(PyValueError::new_err(format!("Connection is locked: {connection:?}")));
-        }
-        commands.entity(entity).set_parent(*to);
-        Ok(())
-    }).execute()
+    action(py, || actions::r#move(connection))?.map_err(|e| e.into_py_err(py))
 }
 
+/// Picks up the item in the current room and returns it, or returns `None` if there is nothing to pick up.
 #[pyfunction]
-fn pickup(py: Python) -> Option<Item> {
-    py.allow_threads(tick);
-    Run::new(move |mut commands: Commands, room: Single<&Parent, With<Character>>, items: Query<(Entity, &Parent, &Item), Without<Character>>| {
-        if let Some((entity, _, item)) = items.iter().find(|(_, parent, _)| parent.get() == room.get()) {
-            commands.entity(entity).despawn();
-            Some(item.clone())
-        } else {
-            None
-        }
-    }).execute()
+fn pickup(py: Python) -> PyResult<Option<Item>> {
+    action(py, actions::pickup)
+}
+
+/// Returns the name of the current room, or `None` if the room has no name.
+#[pyfunction]
+fn r#where(py: Python) -> PyResult<Option<String>> {
+    action(py, actions::r#where)
+}
+
+/// Registers a function to be called with the room name, as returned by `where()`, whenever the character enters a room.
+///
+/// Use `@on_enter` to be called for every room, or `@on_enter(room)` to be called for a single room.
+#[pyfunction]
+#[pyo3(text_signature = "(room)")]
+fn on_enter<'py>(py: Python<'py>, room: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    callbacks::on_enter(py, room)
+}
+
+/// Registers a function to be called with the item whenever an item is picked up.
+#[pyfunction]
+#[pyo3(text_signature = "(callback)")]
+fn on_pickup(callback: Bound<PyAny>) -> PyResult<Bound<PyAny>> {
+    callbacks::on_pickup(callback)
+}
+
+/// Registers a function to be called once every tick after the program has finished, until it is stopped.
+#[pyfunction]
+#[pyo3(text_signature = "(callback)")]
+fn on_tick(callback: Bound<PyAny>) -> PyResult<Bound<PyAny>> {
+    callbacks::on_tick(callback)
 }
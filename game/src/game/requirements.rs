@@ -0,0 +1,79 @@
+#[cfg(feature = "python")]
+use std::collections::HashSet;
+#[cfg(feature = "python")]
+use pyo3::{PyResult, Python};
+#[cfg(feature = "python")]
+use pyo3::exceptions::PySyntaxError;
+#[cfg(feature = "python")]
+use pyo3::types::{PyAnyMethods, PyTypeMethods};
+#[cfg(feature = "python")]
+use crate::game::execution::run::SourceFile;
+#[cfg(feature = "python")]
+use crate::game::level::Requirements;
+
+const CONSTRUCTS: &[(&str, &[&str])] = &[
+    ("for", &["For", "AsyncFor"]),
+    ("while", &["While"]),
+    ("if", &["If", "IfExp"]),
+    ("def", &["FunctionDef", "AsyncFunctionDef"]),
+    ("class", &["ClassDef"]),
+    ("lambda", &["Lambda"]),
+    ("return", &["Return"]),
+    ("break", &["Break"]),
+    ("continue", &["Continue"]),
+    ("try", &["Try", "TryStar"]),
+    ("with", &["With", "AsyncWith"]),
+    ("import", &["Import", "ImportFrom"]),
+    ("global", &["Global", "Nonlocal"]),
+    ("comprehension", &["ListComp", "SetComp", "DictComp", "GeneratorExp"]),
+];
+
+pub fn is_construct(name: &str) -> bool {
+    CONSTRUCTS.iter().any(|(construct, _)| *construct == name)
+}
+
+pub fn constructs() -> impl Iterator<Item = &'static str> {
+    CONSTRUCTS.iter().map(|(construct, _)| *construct)
+}
+
+#[cfg(feature = "python")]
+fn node_types(construct: &str) -> &'static [&'static str] {
+    CONSTRUCTS.iter()
+        .find(|(name, _)| *name == construct)
+        .map_or(&[], |(_, types)| types)
+}
+
+#[cfg(feature = "python")]
+pub fn check(py: Python, requirements: &Requirements, files: &[SourceFile]) -> PyResult<Vec<String>> {
+    let ast = py.import("ast")?;
+    let statement = ast.getattr("stmt")?;
+    let mut found = HashSet::new();
+    let mut statements = 0;
+    for file in files {
+        let tree = match ast.call_method1("parse", (&file.text, &file.name)) {
+            Ok(tree) => tree,
+            // The run reports syntax errors itself
+            Err(e) if e.is_instance_of::<PySyntaxError>(py) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        for node in ast.call_method1("walk", (tree,))?.try_iter()? {
+            let node = node?;
+            found.insert(node.get_type().name()?.to_string());
+            if node.is_instance(&statement)? {
+                statements += 1;
+            }
+        }
+    }
+    let uses = |construct: &str| node_types(construct).iter().any(|node| found.contains(*node));
+    let mut violations = Vec::new();
+    for construct in requirements.uses.iter().filter(|construct| !uses(construct)) {
+        violations.push(format!("Requirement not met: the solution must use `{construct}`"));
+    }
+    for construct in requirements.forbids.iter().filter(|construct| uses(construct)) {
+        violations.push(format!("Requirement not met: the solution must not use `{construct}`"));
+    }
+    if let Some(max) = requirements.max_statements && statements > max {
+        violations.push(format!("Requirement not met: the solution must have at most {max} statements, but has {statements}"));
+    }
+    Ok(violations)
+}
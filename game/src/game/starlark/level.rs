@@ -1,9 +1,13 @@
 use std::cell::{Ref, RefCell};
 use super::room::Room;
+use anyhow::bail;
 use pythoneer_macros::class;
+use starlark::values::list_or_tuple::UnpackListOrTuple;
 use starlark::values::tuple::UnpackTuple;
 use starlark::values::{UnpackValue, Value, ValueError};
 use starlark::values::ValueLike;
+use starlark::values::none::NoneType;
+use crate::game::{level, requirements};
 
 fn get_or_init<T>(cell: &RefCell<Option<T>>, init: impl FnOnce() -> T) -> Ref<T> {
     let option = cell.borrow();
@@ -24,6 +28,15 @@ class! {
         let initial_pan_y: Option<i32> =;
         let initial_zoom: Option<u32> =;
         let keys: Option<Value> =;
+        let par_moves: Option<u32> =;
+        let par_keys: Option<u32> =;
+        let par_pickups: Option<u32> =;
+        let par_ticks: Option<u32> =;
+        let par_lines: Option<u32> =;
+        let uses: Vec<String> =;
+        let forbids: Vec<String> =;
+        let max_statements: Option<u32> =;
+        let fog: bool =;
 
         mut start {
             value.downcast_ref_err::<Room::Mut>()?;
@@ -60,12 +73,45 @@ class! {
             Ok(())
         }
 
+        mut fog {
+            *self.fog.borrow_mut() = value.unpack_bool().ok_or(ValueError::IncorrectParameterType)?;
+            Ok(())
+        }
+
         pub keys {
             Some(*get_or_init(&self.keys, || heap.alloc_complex(Keys::new())))
         }
 
-        fn room(pos: (i32, i32), size: (u32, u32)) -> Value<'v> {
-            let room = heap.alloc(Room::new(pos, size));
+        fn par(#[starlark(require = named)] moves: Option<u32>, #[starlark(require = named)] keys: Option<u32>, #[starlark(require = named)] pickups: Option<u32>, #[starlark(require = named)] ticks: Option<u32>, #[starlark(require = named)] lines: Option<u32>) -> NoneType {
+            *this.par_moves.borrow_mut() = moves;
+            *this.par_keys.borrow_mut() = keys;
+            *this.par_pickups.borrow_mut() = pickups;
+            *this.par_ticks.borrow_mut() = ticks;
+            *this.par_lines.borrow_mut() = lines;
+            Ok(NoneType)
+        }
+
+        fn require(#[starlark(require = named)] uses: Option<UnpackListOrTuple<String>>, #[starlark(require = named)] forbids: Option<UnpackListOrTuple<String>>, #[starlark(require = named)] max_statements: Option<u32>) -> NoneType {
+            let uses = uses.map_or_else(Vec::new, |uses| uses.items);
+            let forbids = forbids.map_or_else(Vec::new, |forbids| forbids.items);
+            if let Some(construct) = uses.iter().chain(&forbids).find(|construct| !requirements::is_construct(construct)) {
+                bail!("Unknown construct {construct:?}, expected one of {}", requirements::constructs().collect::<Vec<_>>().join(", "));
+            }
+            *this.uses.borrow_mut() = uses;
+            *this.forbids.borrow_mut() = forbids;
+            *this.max_statements.borrow_mut() = max_statements;
+            Ok(NoneType)
+        }
+
+        fn room(pos: (i32, i32), size: (u32, u32), #[starlark(require = named)] name: Option<String>, #[starlark(require = named)] colour: Option<String>, #[starlark(require = named)] label: Option<String>) -> Value<'v> {
+            if let Some(colour) = colour.as_deref().filter(|colour| level::colour(colour).is_none()) {
+                bail!("Invalid colour {colour:?}, expected a key colour name or a hex code like \"#335577\"");
+            }
+            let room = Room::new(pos, size);
+            *room.name.borrow_mut() = name;
+            *room.colour.borrow_mut() = colour;
+            *room.label.borrow_mut() = label;
+            let room = heap.alloc(room);
             this.rooms.borrow_mut().push(room);
             Ok(room)
         }
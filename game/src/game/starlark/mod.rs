@@ -1,5 +1,6 @@
 pub mod level;
 pub mod room;
+pub mod player;
 
 use starlark::syntax::{Dialect, DialectTypes};
 
@@ -0,0 +1,159 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+use anyhow::anyhow;
+use bevy::log::error;
+use bevy::prelude::ResMut;
+use pythoneer_macros::class;
+use starlark::codemap::{FileSpan, FileSpanRef};
+use starlark::environment::{FrozenModule, Globals, GlobalsBuilder, LibraryExtension, Module};
+use starlark::eval::{BeforeStmtFuncDyn, Evaluator, FileLoader};
+use starlark::PrintHandler;
+use starlark::starlark_module;
+use starlark::syntax::{AstModule, Dialect};
+use starlark::values::{Heap, Value};
+use starlark::values::none::{NoneOr, NoneType};
+use crate::game::actions;
+use crate::game::execution::channel::Run;
+use crate::game::execution::context::ExecutionContext;
+use crate::game::execution::run::{self, SourceFile};
+use crate::game::level::Item;
+use crate::game::logging::{Log, Stream};
+use super::DIALECT;
+
+class! {
+    pub Key {
+        let name: String;
+
+        fn r#use(connection: String) -> NoneType {
+            actions::use_key(this.name.borrow().clone(), connection)?;
+            Ok(NoneType)
+        }
+    }
+}
+
+#[starlark_module]
+fn player(builder: &mut GlobalsBuilder) {
+    fn r#move(connection: String) -> anyhow::Result<NoneType> {
+        actions::r#move(connection)?;
+        Ok(NoneType)
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn pickup<'v>(heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        Ok(match actions::pickup() {
+            Some(Item::Key(key)) => heap.alloc(Key::new(key.0)),
+            None => Value::new_none(),
+        })
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn r#where() -> anyhow::Result<NoneOr<String>> {
+        Ok(NoneOr::from_option(actions::r#where()))
+    }
+}
+
+struct Printer;
+
+impl PrintHandler for Printer {
+    fn println(&self, text: &str) -> starlark::Result<()> {
+        let text = format!("{text}\n");
+        Run::new(move |mut log: ResMut<Log>| log.push(Stream::Stdout, &text)).execute();
+        Ok(())
+    }
+}
+
+struct Debugger {
+    context: Arc<ExecutionContext>,
+    // Top-level statements are reported twice, the first time before a possible garbage collection
+    statements: Vec<FileSpan>,
+    last: Option<FileSpan>,
+}
+
+impl Debugger {
+    fn new(context: Arc<ExecutionContext>, ast: &AstModule) -> Self {
+        let mut end = None;
+        let statements = ast.stmt_locations().into_iter()
+            .filter(|location| {
+                let top_level = end.is_none_or(|end| location.span.begin() >= end);
+                if top_level {
+                    end = Some(location.span.end());
+                }
+                top_level
+            })
+            .collect();
+        Self { context, statements, last: None }
+    }
+}
+
+impl<'a, 'e: 'a> BeforeStmtFuncDyn<'a, 'e> for Debugger {
+    fn call(&mut self, span: FileSpanRef, eval: &mut Evaluator<'_, 'a, 'e>) -> starlark::Result<()> {
+        let location = span.to_file_span();
+        if self.last.take().is_none_or(|last| last != location) {
+            if self.statements.contains(&location) {
+                self.last = Some(location);
+            }
+            run::line(span.filename(), span.resolve_span().begin.line + 1, eval.call_stack_count());
+        }
+        if self.context.is_cancelled() {
+            return Err(starlark::Error::new_other(anyhow!("KeyboardInterrupt")));
+        }
+        Ok(())
+    }
+}
+
+pub const PLAYER_DIALECT: Dialect = Dialect {
+    enable_load: true,
+    ..DIALECT
+};
+
+struct Loader<'a> {
+    files: &'a [SourceFile],
+    globals: &'a Globals,
+    context: Arc<ExecutionContext>,
+    loading: RefCell<Vec<String>>,
+}
+
+impl Loader<'_> {
+    fn evaluate(&self, module: &Module, file: &SourceFile) -> starlark::Result<()> {
+        let ast = AstModule::parse(&format!("{}.star", file.name), file.text.clone(), &PLAYER_DIALECT)?;
+        let mut eval = Evaluator::new(module);
+        eval.set_loader(self);
+        eval.set_print_handler(&Printer);
+        eval.before_stmt_for_dap((Box::new(Debugger::new(self.context.clone(), &ast)) as Box<dyn BeforeStmtFuncDyn>).into());
+        eval.eval_module(ast, self.globals)?;
+        Ok(())
+    }
+}
+
+impl FileLoader for Loader<'_> {
+    fn load(&self, path: &str) -> starlark::Result<FrozenModule> {
+        let name = path.strip_suffix(".star").unwrap_or(path);
+        let Some(file) = self.files.iter().skip(1).find(|file| file.name == name) else {
+            return Err(starlark::Error::new_other(anyhow!("No file named {path:?}")));
+        };
+        if self.loading.borrow().contains(&file.name) {
+            return Err(starlark::Error::new_other(anyhow!("Circular load of {path:?}")));
+        }
+        self.loading.borrow_mut().push(file.name.clone());
+        let module = Module::new();
+        let result = self.evaluate(&module, file);
+        self.loading.borrow_mut().pop();
+        result?;
+        Ok(module.freeze()?)
+    }
+}
+
+pub fn execute(files: &[SourceFile]) {
+    let Some(main) = files.first() else { return; };
+    let loader = Loader {
+        files,
+        globals: &GlobalsBuilder::extended_by(&[LibraryExtension::Print]).with(player).build(),
+        context: ExecutionContext::current().expect("Starlark program run outside of an execution context"),
+        loading: RefCell::new(Vec::new()),
+    };
+    if let Err(e) = loader.evaluate(&Module::new(), main) {
+        let message = format!("{e}\n");
+        error!("{message}");
+        Run::new(move |mut log: ResMut<Log>| log.push(Stream::Stderr, &message)).execute();
+    }
+}
@@ -10,6 +10,9 @@ class! {
         let size: (u32, u32);
         let connections: Vec<Connection::ClassV> =;
         let item: Option<Value> =;
+        let name: Option<String> =;
+        let colour: Option<String> =;
+        let label: Option<String> =;
 
         mut item {
             if value.is_none() {
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use bevy::app::{App, Plugin};
+use bevy::prelude::{in_state, IntoSystemConfigs, Res, ResMut, Resource, Time, Update};
+use crate::game::execution::context::ExecutionContext;
+use crate::game::execution::execution_state::ExecutionState;
+use crate::game::execution::run::SourceFile;
+
+pub(super) struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Statistics>()
+            .add_systems(Update, measure.run_if(in_state(ExecutionState::Running)));
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct Statistics {
+    pub moves: u32,
+    pub keys: u32,
+    pub pickups: u32,
+    pub ticks: u32,
+    pub lines: u32,
+    /// Excludes the time spent paused, so stepping through the program doesn't count.
+    pub run_time: Duration,
+    pub hits: HashMap<String, HashMap<usize, u32>>,
+}
+
+impl Statistics {
+    pub fn start(files: &[SourceFile]) -> Self {
+        let lines = files.iter()
+            .flat_map(|file| file.text.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .count();
+        Self {
+            lines: u32::try_from(lines).unwrap_or(u32::MAX),
+            ..Self::default()
+        }
+    }
+}
+
+/// Whether actions of the calling program count towards the statistics, which console input doesn't.
+pub fn counted() -> bool {
+    ExecutionContext::current().is_none_or(|context| !context.is_repl())
+}
+
+fn measure(mut statistics: ResMut<Statistics>, time: Res<Time>) {
+    statistics.run_time += time.delta();
+}
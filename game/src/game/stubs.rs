@@ -0,0 +1,125 @@
+use bevy::prelude::Resource;
+#[cfg(feature = "python")]
+use std::fmt::Write;
+#[cfg(feature = "python")]
+use bevy::log::error;
+#[cfg(feature = "python")]
+use bevy::prelude::Commands;
+#[cfg(feature = "python")]
+use pyo3::types::{PyAnyMethods, PyType, PyTypeMethods};
+#[cfg(feature = "python")]
+use pyo3::{Bound, PyAny, PyResult, Python};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "python"), allow(dead_code))]
+pub enum StubKind {
+    Function,
+    Class,
+    Method,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stub {
+    pub name: String,
+    pub kind: StubKind,
+    pub bases: Vec<String>,
+    pub signature: String,
+    pub doc: String,
+}
+
+impl Stub {
+    pub fn short_name(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+
+    pub fn header(&self) -> String {
+        match self.kind {
+            StubKind::Class if self.bases.is_empty() => format!("class {}", self.name),
+            StubKind::Class => format!("class {}({})", self.name, self.bases.join(", ")),
+            StubKind::Function | StubKind::Method => format!("def {}{}", self.name, self.signature),
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Stubs {
+    pub source: String,
+    pub items: Vec<Stub>,
+}
+
+#[cfg(feature = "python")]
+impl Stubs {
+    fn generate(py: Python) -> PyResult<Self> {
+        let module = py.import("pythoneer")?;
+        let mut items = Vec::new();
+        for name in module.getattr("__all__")?.extract::<Vec<String>>()? {
+            let value = module.getattr(name.as_str())?;
+            if let Ok(class) = value.downcast::<PyType>() {
+                let bases = class.getattr("__bases__")?.extract::<Vec<Bound<PyType>>>()?.iter()
+                    .map(|base| base.name().map(|name| name.to_string()))
+                    .filter(|name| name.as_ref().is_ok_and(|name| name != "object"))
+                    .collect::<PyResult<_>>()?;
+                items.push(Stub { name: name.clone(), kind: StubKind::Class, bases, signature: String::new(), doc: doc(&value)? });
+                for entry in class.getattr("__dict__")?.call_method0("items")?.try_iter()? {
+                    let (attribute, method) = entry?.extract::<(String, Bound<PyAny>)>()?;
+                    if attribute.starts_with('_') || !method.is_callable() {
+                        continue;
+                    }
+                    items.push(Stub {
+                        name: format!("{name}.{attribute}"),
+                        kind: StubKind::Method,
+                        bases: Vec::new(),
+                        signature: signature(&method)?.replace("$self", "self"),
+                        doc: doc(&method)?,
+                    });
+                }
+            } else if value.is_callable() {
+                items.push(Stub { name, kind: StubKind::Function, bases: Vec::new(), signature: signature(&value)?, doc: doc(&value)? });
+            }
+        }
+        Ok(Self { source: render(&items), items })
+    }
+}
+
+#[cfg(feature = "python")]
+fn doc(value: &Bound<PyAny>) -> PyResult<String> {
+    Ok(value.getattr("__doc__")?.extract::<Option<String>>()?.unwrap_or_default())
+}
+
+#[cfg(feature = "python")]
+fn signature(value: &Bound<PyAny>) -> PyResult<String> {
+    Ok(value.getattr("__text_signature__")?.extract::<Option<String>>()?.unwrap_or_else(|| "(*args, **kwargs)".to_string()))
+}
+
+#[cfg(feature = "python")]
+fn render(items: &[Stub]) -> String {
+    let mut source = String::new();
+    for item in items {
+        let (indent, header) = match item.kind {
+            StubKind::Method => ("    ", format!("def {}{}", item.short_name(), item.signature)),
+            StubKind::Function | StubKind::Class => ("", item.header()),
+        };
+        let _ = writeln!(source, "{indent}{header}:");
+        if item.doc.is_empty() {
+            let _ = writeln!(source, "{indent}    ...");
+        } else {
+            let doc: Vec<_> = item.doc.lines()
+                .map(|line| if line.is_empty() { String::new() } else { format!("{indent}    {line}") })
+                .collect();
+            let _ = writeln!(source, "{indent}    \"\"\"{}\"\"\"", doc.join("\n").trim_start());
+        }
+        let _ = writeln!(source);
+    }
+    source
+}
+
+#[cfg(feature = "python")]
+pub fn generate(mut commands: Commands) {
+    match Python::with_gil(Stubs::generate) {
+        Ok(stubs) => commands.insert_resource(stubs),
+        Err(e) => {
+            error!("Failed to generate stubs: {e}");
+            commands.init_resource::<Stubs>();
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::app::{App, Plugin};
+use bevy::asset::Assets;
+use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt, Parent};
+use bevy::prelude::{Commands, Entity, In, Mesh, OnEnter, Query, ResMut, Resource, Single, Transform, With, Without};
+use bevy::sprite::ColorMaterial;
+use crate::game::animation::Tween;
+use crate::game::execution::execution_state::ExecutionState;
+use crate::game::level::{item_bundle, Character, Connection, Item, Room, Visited, CHARACTER_Z};
+
+pub(super) struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Timeline>()
+            .add_systems(OnEnter(ExecutionState::Finished), record);
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Timeline {
+    snapshots: Vec<Snapshot>,
+    pub current: usize,
+}
+
+impl Timeline {
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.current = 0;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    room: Entity,
+    locks: Vec<(Entity, bool)>,
+    items: Vec<(Entity, Item)>,
+    visited: Vec<Entity>,
+}
+
+pub fn record(
+    mut timeline: ResMut<Timeline>,
+    character: Single<&Parent, With<Character>>,
+    connections: Query<(Entity, &Connection)>,
+    items: Query<(&Parent, &Item), Without<Character>>,
+    visited: Query<Entity, With<Visited>>,
+) {
+    let snapshot = Snapshot {
+        room: character.get(),
+        locks: connections.iter().map(|(entity, connection)| (entity, connection.locked)).collect(),
+        items: items.iter().map(|(parent, item)| (parent.get(), item.clone())).collect(),
+        visited: visited.iter().collect(),
+    };
+    timeline.current = timeline.snapshots.len();
+    timeline.snapshots.push(snapshot);
+}
+
+pub fn restore(
+    index: In<usize>,
+    mut commands: Commands,
+    mut timeline: ResMut<Timeline>,
+    character: Single<Entity, With<Character>>,
+    rooms: Query<(Entity, &Room)>,
+    mut connections: Query<&mut Connection>,
+    items: Query<Entity, With<Item>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Some(snapshot) = timeline.snapshots.get(*index) else { return; };
+    commands.entity(*character)
+        .set_parent(snapshot.room)
+        .remove::<Tween>()
+        .insert(Transform::from_xyz(0., 0., CHARACTER_Z));
+    for (entity, locked) in &snapshot.locks {
+        if let Ok(mut connection) = connections.get_mut(*entity) {
+            connection.locked = *locked;
+        }
+    }
+    for entity in &items {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (entity, _) in &rooms {
+        if snapshot.visited.contains(&entity) {
+            commands.entity(entity).insert(Visited);
+        } else {
+            commands.entity(entity).remove::<Visited>();
+        }
+    }
+    for (room, item) in &snapshot.items {
+        if let Ok((_, room_data)) = rooms.get(*room) {
+            commands.entity(*room).with_child(item_bundle(item, room_data, &mut meshes, &mut materials));
+        }
+    }
+    timeline.current = *index;
+}
@@ -8,7 +8,7 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_possible_wrap)]
 #![allow(clippy::too_many_arguments)]
-#![feature(custom_inner_attributes)]
+#![cfg_attr(feature = "python", feature(custom_inner_attributes))]
 #![feature(proc_macro_hygiene)]
 #![feature(never_type)]
 #![feature(lock_value_accessors)]
@@ -27,24 +27,33 @@ mod camera;
 mod debug;
 mod geometry;
 
+#[cfg(feature = "python")]
 use crate::game::python;
 use bevy::app::App;
 use bevy::prelude::AppExtStates;
 use bevy::DefaultPlugins;
 use bevy_egui::EguiPlugin;
+#[cfg(feature = "python")]
 use pyo3::types::PyAnyMethods;
+#[cfg(feature = "python")]
 use pyo3::{append_to_inittab, prepare_freethreaded_python, Python};
 use game::execution::execution_state;
 
-fn main() {
+#[cfg(feature = "python")]
+fn init_python() {
     append_to_inittab!(python);
     prepare_freethreaded_python();
     Python::with_gil(|py| {
         let sys = py.import("sys").unwrap();
-        sys.setattr("stdout", game::logging::Logger).unwrap();
-        sys.setattr("stderr", game::logging::Logger).unwrap();
-        sys.setattr("stdin", Option::<()>::None).unwrap();
+        sys.setattr("stdout", game::logging::Logger(game::logging::Stream::Stdout)).unwrap();
+        sys.setattr("stderr", game::logging::Logger(game::logging::Stream::Stderr)).unwrap();
+        sys.setattr("stdin", game::logging::Stdin).unwrap();
     });
+}
+
+fn main() {
+    #[cfg(feature = "python")]
+    init_python();
     let mut app = App::new();
     app.add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin)
@@ -0,0 +1,101 @@
+use std::fs;
+use bevy::log::error;
+use bevy::prelude::{Commands, ResMut, Resource};
+use bevy_egui::egui;
+use bevy_egui::egui::{Frame, Margin, RichText, ScrollArea, Stroke};
+use egui_extras::syntax_highlighting;
+use crate::game::execution::syntax::SyntaxError;
+use crate::game::level::{self, CurrentLevel};
+use crate::ui::egui::id;
+use super::ui::{error_range, underline};
+
+#[derive(Resource, Default)]
+pub(super) struct Author {
+    pub(super) open: bool,
+    text: String,
+    synced: String,
+    error: Option<(SyntaxError, bool)>,
+}
+
+impl Author {
+    fn apply(&mut self, commands: &mut Commands, current_level: &mut ResMut<CurrentLevel>) {
+        match level::parse(&current_level.0.id, &self.text) {
+            Ok(mut level) => {
+                level.path.clone_from(&current_level.0.path);
+                self.synced.clone_from(&self.text);
+                self.error = None;
+                let changed = !level.same_layout(&current_level.0);
+                **current_level = CurrentLevel(level);
+                if changed {
+                    commands.run_system_cached(level::reset);
+                }
+            }
+            Err(e) => self.error = Some((SyntaxError::from(&e), e.span().is_some())),
+        }
+    }
+}
+
+pub(super) fn window(ctx: &egui::Context, commands: &mut Commands, author: &mut Author, current_level: &mut ResMut<CurrentLevel>, interactive: bool) {
+    if author.synced != current_level.0.source {
+        author.synced.clone_from(&current_level.0.source);
+        author.text.clone_from(&current_level.0.source);
+        author.error = None;
+    }
+    let mut open = author.open;
+    egui::Window::new("Level Script")
+        .id(id!())
+        .open(&mut open)
+        .default_size([480., 480.])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Save…").clicked() && let Some(path) = rfd::FileDialog::new()
+                    .set_title("Save Level")
+                    .add_filter("Pythoneer Level", &["plvl"])
+                    .set_file_name(format!("{}.plvl", current_level.0.id))
+                    .save_file()
+                    && let Err(e) = fs::write(&path, &author.text) {
+                    error!("Failed to save {}: {e}", path.display());
+                }
+                if !interactive {
+                    ui.weak("Stop the program to edit the level");
+                }
+            });
+            if let Some((error, spanned)) = &author.error {
+                let text = if *spanned { format!("{}:{}: {}", error.line, error.column + 1, error.message) } else { error.message.clone() };
+                ui.label(RichText::new(text).monospace().color(ui.visuals().error_fg_color));
+            }
+            let underlined = author.error.as_ref().filter(|(_, spanned)| *spanned).map(|(error, _)| error.clone());
+            Frame::canvas(ui.style()).show(ui, |ui| {
+                ScrollArea::both().show(ui, |ui| {
+                    let output = egui::TextEdit::multiline(&mut author.text)
+                        .id(id!())
+                        .interactive(interactive)
+                        .font(egui::TextStyle::Monospace)
+                        .code_editor()
+                        .desired_rows(20)
+                        .desired_width(f32::INFINITY)
+                        .frame(false)
+                        .margin(Margin::same(0))
+                        .layouter(&mut |ui: &egui::Ui, string, _wrap_width| {
+                            let mut layout_job = syntax_highlighting::highlight(
+                                ui.ctx(),
+                                ui.style(),
+                                &syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style()),
+                                string,
+                                "py",
+                            );
+                            layout_job.wrap.max_width = f32::INFINITY;
+                            if let Some(error) = &underlined {
+                                underline(&mut layout_job, error_range(string, error), Stroke::new(1.5_f32, ui.visuals().error_fg_color));
+                            }
+                            ui.fonts(|f| f.layout_job(layout_job))
+                        })
+                        .show(ui);
+                    if output.response.changed() {
+                        author.apply(commands, current_level);
+                    }
+                });
+            });
+        });
+    author.open = open;
+}
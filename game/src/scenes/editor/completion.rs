@@ -0,0 +1,125 @@
+use bevy::prelude::Resource;
+use bevy_egui::egui;
+use bevy_egui::egui::text::{CCursor, CCursorRange};
+use bevy_egui::egui::text_edit::{TextEditOutput, TextEditState};
+use bevy_egui::egui::{Area, Frame, Id, Key, Modifiers, Order, RichText};
+use crate::game::stubs::{Stub, StubKind, Stubs};
+
+#[derive(Resource, Default)]
+pub(super) struct Completion {
+    candidates: Vec<usize>,
+    selected: usize,
+    cursor: usize,
+    prefix: usize,
+    dismissed: Option<usize>,
+    clicked: Option<usize>,
+    navigated: bool,
+}
+
+impl Completion {
+    pub(super) fn before(&mut self, ui: &mut egui::Ui, id: Id, code: &mut String, stubs: &Stubs) -> bool {
+        if self.candidates.is_empty() || !ui.memory(|memory| memory.has_focus(id)) && self.clicked.is_none() {
+            self.navigated = false;
+            return false;
+        }
+        let accept = ui.input_mut(|input| {
+            if input.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                self.selected = (self.selected + 1) % self.candidates.len();
+                self.navigated = true;
+            }
+            if input.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                self.selected = (self.selected + self.candidates.len() - 1) % self.candidates.len();
+                self.navigated = true;
+            }
+            if input.consume_key(Modifiers::NONE, Key::Escape) {
+                self.dismissed = Some(self.cursor);
+            }
+            input.consume_key(Modifiers::NONE, Key::Tab) || self.navigated && input.consume_key(Modifiers::NONE, Key::Enter)
+        });
+        let Some(selected) = self.clicked.take().or(accept.then_some(self.selected)) else { return false; };
+        let Some(stub) = self.candidates.get(selected).and_then(|index| stubs.items.get(*index)) else { return false; };
+        let completion: String = stub.short_name().chars().skip(self.prefix).collect();
+        let byte = code.char_indices().nth(self.cursor).map_or(code.len(), |(byte, _)| byte);
+        code.insert_str(byte, &completion);
+        let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_default();
+        state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(self.cursor + completion.chars().count()))));
+        state.store(ui.ctx(), id);
+        ui.memory_mut(|memory| memory.request_focus(id));
+        self.candidates.clear();
+        self.navigated = false;
+        true
+    }
+
+    pub(super) fn after(&mut self, ui: &egui::Ui, output: &TextEditOutput, code: &str, stubs: &Stubs) {
+        self.candidates.clear();
+        let Some(range) = output.cursor_range.filter(|_| output.response.has_focus()) else { return; };
+        let cursor = range.primary.ccursor.index;
+        if !range.is_empty() || self.dismissed == Some(cursor) {
+            return;
+        }
+        self.dismissed = None;
+        let before: Vec<char> = code.chars().take(cursor).collect();
+        let prefix: String = before.iter().rev()
+            .take_while(|c| c.is_alphanumeric() || **c == '_')
+            .collect::<Vec<_>>().into_iter().rev().collect();
+        let length = prefix.chars().count();
+        let member = before.len() > length && before[before.len() - length - 1] == '.';
+        if prefix.is_empty() && !member {
+            return;
+        }
+        self.candidates = stubs.items.iter().enumerate()
+            .filter(|(_, stub)| (stub.kind == StubKind::Method) == member)
+            .filter(|(_, stub)| stub.short_name().starts_with(&prefix) && stub.short_name() != prefix)
+            .map(|(i, _)| i)
+            .collect();
+        if self.candidates.is_empty() {
+            return;
+        }
+        self.selected = self.selected.min(self.candidates.len() - 1);
+        self.navigated &= self.cursor == cursor;
+        self.cursor = cursor;
+        self.prefix = length;
+        let position = output.galley.pos_from_ccursor(range.primary.ccursor).translate(output.galley_pos.to_vec2()).left_bottom();
+        Area::new(output.response.id.with("completion"))
+            .order(Order::Foreground)
+            .fixed_pos(position)
+            .show(ui.ctx(), |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    for (i, index) in self.candidates.iter().enumerate() {
+                        let stub = &stubs.items[*index];
+                        let response = ui.selectable_label(i == self.selected, RichText::new(stub.header()).monospace());
+                        if response.clicked() {
+                            self.clicked = Some(i);
+                        }
+                        if i == self.selected && !stub.doc.is_empty() {
+                            response.on_hover_text(&stub.doc);
+                        }
+                    }
+                });
+            });
+    }
+}
+
+pub(super) fn hover(output: &TextEditOutput, code: &str, stubs: &Stubs) {
+    let Some(pointer) = output.response.hover_pos() else { return; };
+    let index = output.galley.cursor_from_pos(pointer - output.galley_pos).ccursor.index;
+    let chars: Vec<char> = code.chars().collect();
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    let start = chars[..index.min(chars.len())].iter().rposition(|c| !is_word(c)).map_or(0, |i| i + 1);
+    let end = chars[start..].iter().position(|c| !is_word(c)).map_or(chars.len(), |i| start + i);
+    if start == end {
+        return;
+    }
+    let word: String = chars[start..end].iter().collect();
+    let member = start > 0 && chars[start - 1] == '.';
+    let Some(stub) = stubs.items.iter().find(|stub| (stub.kind == StubKind::Method) == member && stub.short_name() == word) else { return; };
+    output.response.clone().on_hover_ui_at_pointer(|ui| documentation(ui, stub));
+}
+
+fn documentation(ui: &mut egui::Ui, stub: &Stub) {
+    ui.label(RichText::new(stub.header()).monospace().strong());
+    if !stub.doc.is_empty() {
+        ui.separator();
+        ui.label(&stub.doc);
+    }
+}
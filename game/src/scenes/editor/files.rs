@@ -0,0 +1,187 @@
+use std::{fs, mem};
+use std::path::{Path, PathBuf};
+use bevy::app::AppExit;
+use bevy::log::{error, warn};
+use bevy::prelude::{EventReader, Local, Res, ResMut, Resource};
+use bevy::time::Time;
+use crate::game::execution::run::{Language, SourceFile};
+use crate::game::level::{CurrentLevel, Level};
+
+const AUTOSAVE_INTERVAL: f32 = 2.;
+
+#[derive(Resource)]
+pub(super) struct Code {
+    pub(super) files: Vec<SourceFile>,
+    pub(super) current: usize,
+    pub(super) language: Language,
+    pub(super) renaming: Option<String>,
+    pub(super) deleting: Option<usize>,
+    pub(super) dirty: bool,
+    removed: Vec<String>,
+}
+
+impl Default for Code {
+    fn default() -> Self {
+        Self {
+            files: vec![SourceFile::new("main", "")],
+            current: 0,
+            language: Language::default(),
+            renaming: None,
+            deleting: None,
+            dirty: false,
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl Code {
+    pub(super) fn filename(&self, index: usize) -> String {
+        format!("{}.{}", self.files[index].name, self.language.extension())
+    }
+
+    pub(super) fn find(&self, filename: &str) -> Option<usize> {
+        (0..self.files.len()).find(|index| self.filename(*index) == filename)
+    }
+
+    pub(super) fn current_mut(&mut self) -> &mut SourceFile {
+        let current = self.current.min(self.files.len() - 1);
+        &mut self.files[current]
+    }
+
+    pub(super) fn is_valid_name(&self, name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_')
+            && self.files.iter().all(|file| file.name != name)
+    }
+
+    pub(super) fn add(&mut self, name: String, text: String) {
+        if let Some(index) = self.files.iter().position(|file| file.name == name) {
+            self.files[index].text = text;
+            self.current = index;
+        } else {
+            self.files.push(SourceFile::new(name, text));
+            self.current = self.files.len() - 1;
+        }
+        self.dirty = true;
+    }
+
+    pub(super) fn rename(&mut self, index: usize, name: String) {
+        self.removed.push(self.filename(index));
+        self.files[index].name = name;
+        self.dirty = true;
+    }
+
+    pub(super) fn remove(&mut self, index: usize) {
+        if index == 0 || index >= self.files.len() {
+            return;
+        }
+        self.removed.push(self.filename(index));
+        self.files.remove(index);
+        self.current = self.current.min(self.files.len() - 1);
+        self.dirty = true;
+    }
+
+    fn load(level: &Level) -> Self {
+        let Some(directory) = directory(level) else { return Self::default(); };
+        let Ok(entries) = fs::read_dir(&directory) else { return Self::default(); };
+        let mut code = Self::default();
+        let mut files: Vec<(PathBuf, Language)> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter_map(|path| Some((path.clone(), language(&path)?)))
+            .collect();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        // Files of both languages can be saved, the one saved last is the one in use
+        let main = files.iter()
+            .filter(|(path, _)| path.file_stem().is_some_and(|stem| stem == "main"))
+            .max_by_key(|(path, _)| fs::metadata(path).and_then(|metadata| metadata.modified()).ok());
+        if let Some((_, language)) = main {
+            code.language = *language;
+        }
+        for (path, language) in &files {
+            if *language != code.language {
+                continue;
+            }
+            let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()) else { continue; };
+            match fs::read_to_string(path) {
+                Ok(text) if name == "main" => code.files[0].text = text,
+                Ok(text) => code.files.push(SourceFile::new(name, text)),
+                Err(e) => warn!("Failed to read {}: {e}", path.display()),
+            }
+        }
+        code
+    }
+
+    fn save(&mut self, level: &Level) {
+        let Some(directory) = directory(level) else {
+            error!("No data directory to save code to");
+            return;
+        };
+        if let Err(e) = fs::create_dir_all(&directory) {
+            error!("Failed to create {}: {e}", directory.display());
+            return;
+        }
+        let filenames: Vec<_> = (0..self.files.len()).map(|index| self.filename(index)).collect();
+        for filename in mem::take(&mut self.removed).into_iter().filter(|removed| !filenames.contains(removed)) {
+            let path = directory.join(filename);
+            if path.exists() && let Err(e) = fs::remove_file(&path) {
+                warn!("Failed to remove {}: {e}", path.display());
+            }
+        }
+        for (file, filename) in self.files.iter().zip(filenames) {
+            if let Err(e) = fs::write(directory.join(&filename), &file.text) {
+                error!("Failed to save {filename}: {e}");
+            }
+        }
+        self.dirty = false;
+    }
+}
+
+fn directory(level: &Level) -> Option<PathBuf> {
+    Some(dirs_next::data_dir()?.join("pythoneer").join("levels").join(key(level)))
+}
+
+/// Levels loaded from files are also keyed by their path, so that files with the same name keep separate code.
+fn key(level: &Level) -> String {
+    let Some(path) = &level.path else { return level.id.clone(); };
+    let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+    let hash = path.to_string_lossy().bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3));
+    format!("{}-{hash:016x}", level.id)
+}
+
+fn language(path: &Path) -> Option<Language> {
+    let extension = path.extension()?;
+    Language::ALL.iter().copied().find(|language| extension == language.extension())
+}
+
+pub(super) fn switch_level(code: &mut Code, from: &Level, to: &Level) {
+    if code.dirty {
+        code.save(from);
+    }
+    *code = Code::load(to);
+}
+
+pub(super) fn load(mut code: ResMut<Code>, level: Res<CurrentLevel>) {
+    *code = Code::load(&level.0);
+}
+
+pub(super) fn save(mut code: ResMut<Code>, level: Res<CurrentLevel>) {
+    if code.dirty {
+        code.save(&level.0);
+    }
+}
+
+pub(super) fn autosave(mut code: ResMut<Code>, level: Res<CurrentLevel>, time: Res<Time>, mut elapsed: Local<f32>) {
+    *elapsed += time.delta_secs();
+    if code.dirty && *elapsed >= AUTOSAVE_INTERVAL {
+        code.save(&level.0);
+        *elapsed = 0.;
+    }
+}
+
+pub(super) fn save_on_exit(events: EventReader<AppExit>, code: ResMut<Code>, level: Res<CurrentLevel>) {
+    if !events.is_empty() {
+        save(code, level);
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::math::{Rect, Vec2};
+use bevy::prelude::{Camera, GlobalTransform, OrthographicProjection, Query, Single, Transform, Visibility, With};
+use bevy::window::{PrimaryWindow, Window};
+use bevy_egui::egui::{self, Area, Color32, Order, Sense, Stroke, StrokeKind};
+use bevy_egui::EguiContexts;
+use crate::camera::ControllableCamera2d;
+use crate::game::level::{Character, Room};
+use crate::ui::egui::id;
+
+const MAX_SIZE: Vec2 = Vec2::new(180., 140.);
+const MARGIN: f32 = 12.;
+
+struct Projection {
+    bounds: Rect,
+    origin: egui::Pos2,
+    scale: f32,
+}
+
+impl Projection {
+    fn to_screen(&self, point: Vec2) -> egui::Pos2 {
+        self.origin + egui::vec2(point.x - self.bounds.min.x, self.bounds.max.y - point.y) * self.scale
+    }
+
+    fn to_world(&self, pos: egui::Pos2) -> Vec2 {
+        let offset = (pos - self.origin) / self.scale;
+        Vec2::new(self.bounds.min.x + offset.x, self.bounds.max.y - offset.y)
+    }
+
+    fn rect(&self, rect: Rect) -> egui::Rect {
+        egui::Rect::from_two_pos(self.to_screen(rect.min), self.to_screen(rect.max))
+    }
+}
+
+pub(super) fn render(
+    mut contexts: EguiContexts,
+    camera: Single<(&Camera, &mut ControllableCamera2d, &mut Transform, &OrthographicProjection)>,
+    rooms: Query<(&Room, &Visibility)>,
+    character: Query<&GlobalTransform, With<Character>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+) {
+    let (camera, mut controls, mut transform, projection) = camera.into_inner();
+    let (Some(viewport), Some(size)) = (camera.viewport.as_ref(), camera.logical_viewport_size()) else { return; };
+    let view = Rect::from_center_size(transform.translation.truncate(), size * projection.scale);
+    let bounds = controls.bounds;
+    if view.contains(bounds.min) && view.contains(bounds.max) || bounds.is_empty() {
+        return;
+    }
+    let scale = (MAX_SIZE / bounds.size()).min_element();
+    let corner = (viewport.physical_position + viewport.physical_size).as_vec2() / window.scale_factor();
+    let origin = corner - bounds.size() * scale - MARGIN;
+    let map = Projection { bounds, origin: egui::pos2(origin.x, origin.y), scale };
+    Area::new(id!())
+        .order(Order::Foreground)
+        .fixed_pos(map.origin)
+        .show(contexts.ctx_mut(), |ui| {
+            let (response, painter) = ui.allocate_painter(egui::vec2(bounds.width(), bounds.height()) * scale, Sense::click_and_drag());
+            painter.rect_filled(response.rect, 4., Color32::from_black_alpha(180));
+            for (room, visibility) in &rooms {
+                if *visibility != Visibility::Hidden {
+                    painter.rect_filled(map.rect(room.rect), 0., Color32::from_gray(90));
+                }
+            }
+            for character in &character {
+                painter.circle_filled(map.to_screen(character.translation().truncate()), 3., Color32::WHITE);
+            }
+            painter.rect_stroke(map.rect(view).intersect(response.rect), 0., Stroke::new(1.5_f32, Color32::YELLOW), StrokeKind::Inside);
+            if let Some(pos) = response.interact_pointer_pos().filter(|_| response.clicked() || response.dragged()) {
+                controls.follow = false;
+                transform.translation = map.to_world(pos).extend(transform.translation.z);
+            }
+        });
+}
@@ -1,11 +1,16 @@
 mod ui;
+mod completion;
+mod files;
+mod syntax;
+mod author;
+mod minimap;
 
 use crate::game::execution::run::run;
-use std::fs;
 use crate::game::level;
 use crate::scenes::Scene;
-use bevy::app::{App, Plugin, Update};
+use bevy::app::{App, Last, Plugin, Update};
 use bevy::prelude::{in_state, Commands, EventReader, IntoSystemConfigs, NextState, OnEnter, OnExit, Res, ResMut, State};
+use bevy::log::error;
 use bevy::window::FileDragAndDrop;
 use crate::game::execution::execution_state::ExecutionState;
 use crate::game::level::{reset, CurrentLevel};
@@ -14,13 +19,21 @@ pub(super) struct EditorPlugin;
 
 impl Plugin for EditorPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ui::Code>()
+        app.init_resource::<files::Code>()
+            .init_resource::<ui::Console>()
+            .init_resource::<completion::Completion>()
+            .init_resource::<syntax::Syntax>()
+            .init_resource::<author::Author>()
             .add_systems(Update, ui::render.run_if(in_state(Scene::Editor)))
+            .add_systems(Update, minimap::render.after(ui::render).run_if(in_state(Scene::Editor)))
             .add_systems(Update, file_drop)
-            .add_systems(OnEnter(Scene::Editor), level::spawn)
-            .add_systems(OnExit(Scene::Editor), level::despawn)
+            .add_systems(Update, files::autosave.run_if(in_state(Scene::Editor)))
+            .add_systems(Update, syntax::check.run_if(in_state(Scene::Editor)))
+            .add_systems(Last, files::save_on_exit.run_if(in_state(Scene::Editor)))
+            .add_systems(OnEnter(Scene::Editor), (level::spawn, files::load))
+            .add_systems(OnExit(Scene::Editor), (level::despawn, files::save))
             .add_systems(OnEnter(ExecutionState::Stopped), reset.run_if(in_state(Scene::Editor)))
-            .add_systems(OnExit(ExecutionState::Stopped), |mut commands: Commands, code: Res<ui::Code>| commands.run_system_cached_with(run, code.0.clone()));
+            .add_systems(OnExit(ExecutionState::Stopped), |mut commands: Commands, code: Res<files::Code>| commands.run_system_cached_with(run, (code.language, code.files.clone())));
     }
 }
 
@@ -29,19 +42,26 @@ fn file_drop(
     mut events: EventReader<FileDragAndDrop>,
     mut next_scene: ResMut<NextState<Scene>>,
     mut current_level: ResMut<CurrentLevel>,
-    mut code: ResMut<ui::Code>,
+    mut code: ResMut<files::Code>,
     mut next_execution: ResMut<NextState<ExecutionState>>,
     scene: Res<State<Scene>>,
 ) {
     for event in events.read() {
         if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
-            let level = level::parse(&fs::read_to_string(path_buf).unwrap());
-            *current_level = CurrentLevel(level);
+            let level = match level::load(path_buf) {
+                Ok(level) => level,
+                Err(e) => {
+                    error!("Failed to load {}: {e}", path_buf.display());
+                    continue;
+                }
+            };
             if *scene == Scene::Editor {
-                code.0.clear();
+                files::switch_level(&mut code, &current_level.0, &level);
+                *current_level = CurrentLevel(level);
                 commands.run_system_cached(level::reset);
                 next_execution.set(ExecutionState::Stopped);
             } else {
+                *current_level = CurrentLevel(level);
                 next_scene.set(Scene::Editor);
             }
         }
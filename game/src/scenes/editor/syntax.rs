@@ -0,0 +1,41 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use bevy::prelude::{Res, ResMut, Resource};
+use bevy::time::Time;
+use crate::game::execution::syntax::{self, SyntaxError};
+use super::files::Code;
+
+const DEBOUNCE: f32 = 0.5;
+
+#[derive(Resource, Default)]
+pub(super) struct Syntax {
+    pub(super) errors: Vec<Option<SyntaxError>>,
+    seen: Option<u64>,
+    checked: Option<u64>,
+    elapsed: f32,
+}
+
+impl Syntax {
+    pub(super) fn compiles(&self) -> bool {
+        self.checked.is_some() && self.checked == self.seen && self.errors.iter().all(Option::is_none)
+    }
+
+    pub(super) fn error(&self, index: usize) -> Option<&SyntaxError> {
+        self.errors.get(index).and_then(Option::as_ref)
+    }
+}
+
+pub(super) fn check(mut syntax: ResMut<Syntax>, code: Res<Code>, time: Res<Time>) {
+    let mut hasher = DefaultHasher::new();
+    (code.language, &code.files).hash(&mut hasher);
+    let hash = hasher.finish();
+    if syntax.seen != Some(hash) {
+        syntax.seen = Some(hash);
+        syntax.elapsed = 0.;
+        return;
+    }
+    syntax.elapsed += time.delta_secs();
+    if syntax.elapsed >= DEBOUNCE && syntax.checked != Some(hash) {
+        syntax.errors = code.files.iter().map(|file| syntax::check(code.language, file)).collect();
+        syntax.checked = Some(hash);
+    }
+}
@@ -1,72 +1,140 @@
-use crate::game::logging::Log;
+use std::{fs, mem};
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::camera::ControllableCamera2d;
+use crate::game::logging::{traceback_line, InputRequest, Log, Stream};
+use crate::game::timeline::{restore, Timeline};
+use crate::game::stubs::Stubs;
+use crate::game::statistics::Statistics;
+use crate::game::level::{CurrentLevel, Par};
+use super::completion::{hover, Completion};
+use super::files::Code;
+use super::syntax::Syntax;
+use super::author::{self, Author};
+use bevy::ecs::system::SystemParam;
 use bevy::math::UVec2;
-use bevy::prelude::{Camera, NextState, Res, ResMut, Resource, Single, State, With};
+use bevy::prelude::{error, Camera, Commands, DetectChanges, Local, NextState, Query, Res, ResMut, Resource, Single, State, With};
 use bevy::render::camera::Viewport;
 use bevy::window::{PrimaryWindow, Window};
 use bevy_egui::{egui, EguiContexts};
-use bevy_egui::egui::{Frame, Margin, ScrollArea, SidePanel, TopBottomPanel};
+use bevy_egui::egui::{Align, Align2, CollapsingHeader, Rect, ComboBox, CursorIcon, Grid, Frame, Label, Margin, Modal, RichText, ScrollArea, Sense, SidePanel, Slider, Stroke, TopBottomPanel};
+use bevy_egui::egui::text::{CCursor, CCursorRange, LayoutJob};
 use egui_extras::syntax_highlighting;
 use egui_extras::syntax_highlighting::code_view_ui;
 use crate::game::execution::execution_state::ExecutionState;
-use crate::game::execution::run::{reset_tick, STEPPER};
+use crate::game::execution::context::{Execution, StepMode};
+use crate::game::execution::run::Language;
+#[cfg(feature = "python")]
+use crate::game::execution::repl::{self, Repl};
+use crate::game::execution::run::ProgramTask;
+use crate::game::execution::syntax::SyntaxError;
 use crate::scenes::Scene;
 use crate::ui::egui::id;
 
+const GUTTER_WIDTH: f32 = 32.;
+
 #[derive(Resource, Default)]
-pub(super) struct Code(pub(crate) String);
+pub(super) struct Console {
+    input: String,
+    search: String,
+    #[cfg(feature = "python")]
+    history: Vec<String>,
+    #[cfg(feature = "python")]
+    history_index: usize,
+    refocus: bool,
+}
+
+#[derive(SystemParam)]
+pub(super) struct ConsoleParams<'w, 's> {
+    log: ResMut<'w, Log>,
+    console: ResMut<'w, Console>,
+    input_request: Option<ResMut<'w, InputRequest>>,
+    #[cfg(feature = "python")]
+    repl: Option<Res<'w, Repl>>,
+    #[cfg_attr(not(feature = "python"), allow(dead_code))]
+    tasks: Query<'w, 's, (), With<ProgramTask>>,
+}
+
+#[derive(SystemParam)]
+pub(super) struct EditorParams<'w> {
+    code: ResMut<'w, Code>,
+    completion: ResMut<'w, Completion>,
+    stubs: Res<'w, Stubs>,
+    syntax: Res<'w, Syntax>,
+    author: ResMut<'w, Author>,
+}
+
+#[derive(SystemParam)]
+pub(super) struct DebugParams<'w, 's> {
+    executions: Query<'w, 's, &'static Execution>,
+    step_mode: ResMut<'w, StepMode>,
+    cursor: Local<'s, Option<(usize, usize)>>,
+    position: Local<'s, Option<(String, usize)>>,
+}
+
+impl DebugParams<'_, '_> {
+    fn step(&mut self, mode: StepMode) {
+        for Execution(context) in &self.executions {
+            context.set_step_mode(mode.clone());
+            context.reset_tick();
+            context.stepper.wake();
+        }
+        *self.step_mode = mode;
+    }
+
+    fn pause(&mut self) {
+        self.step(StepMode::Action);
+        for Execution(context) in &self.executions {
+            context.request_pause();
+        }
+    }
+
+    fn is_waiting(&self) -> bool {
+        self.executions.iter().any(|Execution(context)| context.stepper.is_waiting())
+    }
+}
+
+#[derive(SystemParam)]
+pub(super) struct ResultsParams<'w> {
+    statistics: Res<'w, Statistics>,
+    level: ResMut<'w, CurrentLevel>,
+}
 
 #[allow(clippy::cast_sign_loss)]
 pub(super) fn render(
+    mut commands: Commands,
     mut contexts: EguiContexts,
-    mut code: ResMut<Code>,
-    log: Res<Log>,
+    mut editor: EditorParams,
+    mut console: ConsoleParams,
+    mut debug: DebugParams,
+    timeline: Res<Timeline>,
+    mut results: ResultsParams,
     execution: Res<State<ExecutionState>>,
     mut next_execution: ResMut<NextState<ExecutionState>>,
     mut next_scene: ResMut<NextState<Scene>>,
-    mut camera: Single<&mut Camera>,
+    mut camera: Single<(&mut Camera, &mut ControllableCamera2d)>,
     window: Single<&mut Window, With<PrimaryWindow>>,
+    mut goto: Local<Option<(usize, usize)>>,
+    mut show_api: Local<bool>,
 ) {
     let bottom = if execution.show_console() {
         TopBottomPanel::bottom(id!())
             .resizable(true)
-            .show(contexts.ctx_mut(), |ui| {
-                Frame::canvas(ui.style())
-                    .show(ui, |ui| {
-                        ScrollArea::both()
-                            .stick_to_bottom(true)
-                            .max_width(f32::INFINITY)
-                            .auto_shrink(false)
-                            .show(ui, |ui| {
-                                code_view_ui(
-                                    ui,
-                                    &syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style()),
-                                    &log.0,
-                                    "",
-                                )
-                            });
-                    });
-            }).response.rect.height() * window.scale_factor()
+            .show(contexts.ctx_mut(), |ui| console_view(ui, &mut commands, &mut console, &editor.code, &mut goto, **execution))
+            .response.rect.height() * window.scale_factor()
     } else { 0. };
+    let position = debug.executions.iter().find_map(|Execution(context)| context.position());
+    if position != *debug.position {
+        if let Some((file, line)) = &position && let Some(index) = editor.code.find(file) {
+            *goto = Some((index, *line));
+        }
+        debug.position.clone_from(&position);
+    }
     let left = SidePanel::left(id!())
         .resizable(true)
         .show(contexts.ctx_mut(), |ui| {
-            ui.horizontal(|ui| {
-                if execution.can_run() && ui.button("Run").clicked() {
-                    reset_tick();
-                    next_execution.set(ExecutionState::Running);
-                    STEPPER.wake();
-                }
-                if *execution == ExecutionState::Stopped && ui.button("Step").clicked() {
-                    next_execution.set(ExecutionState::Stepping);
-                    STEPPER.skip();
-                    STEPPER.wake();
-                } else if *execution == ExecutionState::Stepping {
-                    if !STEPPER.is_waiting() {
-                        ui.add_enabled_ui(false, |ui| ui.button("Step"));
-                    } else if ui.button("Step").clicked() {
-                        STEPPER.wake();
-                    }
-                }
+            ui.horizontal_wrapped(|ui| {
+                controls(ui, **execution, &mut next_execution, &mut debug, &editor.code, editor.syntax.compiles());
                 if execution.can_exit() && ui.button("Exit").clicked() {
                     next_scene.set(Scene::MainMenu);
                 }
@@ -80,35 +148,522 @@ pub(super) fn render(
                 if !execution.interactive() && execution.shutdown() {
                     ui.add_enabled_ui(false, |ui| ui.button("Stopping…"));
                 }
+                if editor.code.language.has_stubs() && !editor.stubs.items.is_empty() {
+                    ui.toggle_value(&mut show_api, "API");
+                }
+                ui.toggle_value(&mut editor.author.open, "Level");
+                if ui.button("Fit").on_hover_text("Fit the level to the view (F)\nPan with the arrow keys, zoom with + and -").clicked() {
+                    camera.1.fit = true;
+                }
+                ui.toggle_value(&mut camera.1.follow, "Follow").on_hover_text("Keep the character in view (C)");
+                ui.add_enabled_ui(execution.interactive(), |ui| {
+                    ComboBox::from_id_salt(id!())
+                        .selected_text(editor.code.language.name())
+                        .show_ui(ui, |ui| {
+                            for &language in Language::ALL {
+                                if ui.selectable_value(&mut editor.code.language, language, language.name()).changed() {
+                                    editor.code.dirty = true;
+                                }
+                            }
+                        });
+                });
             });
+            if *execution == ExecutionState::Finished && !timeline.is_empty() {
+                let mut current = timeline.current;
+                if ui.add(Slider::new(&mut current, 0..=timeline.len() - 1).text("Tick")).changed() {
+                    commands.run_system_cached_with(restore, current);
+                }
+            }
+            if *execution == ExecutionState::Finished {
+                results_view(ui, &results.statistics, &results.level.0.par);
+            }
+            tabs(ui, &mut editor.code, execution.interactive());
+            let target = goto.take();
+            if let Some((index, _)) = target {
+                editor.code.current = index;
+            }
+            let current = editor.code.current;
+            let highlight = position.as_ref()
+                .filter(|(file, _)| editor.code.find(file) == Some(current))
+                .map(|(_, line)| *line);
+            let hits = (*execution == ExecutionState::Finished)
+                .then(|| results.statistics.hits.get(&editor.code.filename(current)))
+                .flatten();
             Frame::canvas(ui.style())
                 .show(ui, |ui| {
                     ScrollArea::both().show(ui, |ui| {
-                        ui.add(egui::TextEdit::multiline(&mut code.0)
-                            .interactive(execution.interactive())
-                            .font(egui::TextStyle::Monospace)
-                            .code_editor()
-                            .desired_rows(10)
-                            .lock_focus(true)
-                            .desired_width(f32::INFINITY)
-                            .frame(false)
-                            .margin(Margin::same(0))
-                            .layouter(&mut |ui: &egui::Ui, string, _wrap_width| {
-                                let mut layout_job = syntax_highlighting::highlight(
-                                    ui.ctx(),
-                                    ui.style(),
-                                    &syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style()),
-                                    string,
-                                    "py",
-                                );
-                                layout_job.wrap.max_width = f32::INFINITY;
-                                ui.fonts(|f| f.layout_job(layout_job))
-                            })
-                        );
+                        let stubs = editor.code.language.has_stubs().then_some(&*editor.stubs);
+                        let text = &mut editor.code.current_mut().text;
+                        let (changed, cursor) = code_editor(ui, text, current, execution.interactive(), target.map(|(_, line)| line), highlight, hits, editor.syntax.error(current), &mut editor.completion, stubs);
+                        if changed {
+                            editor.code.dirty = true;
+                        }
+                        if let Some(line) = cursor {
+                            *debug.cursor = Some((current, line));
+                        }
                     });
                 });
+            syntax_errors(ui, &editor.syntax, &editor.code, &mut goto);
         }).response.rect.width() * window.scale_factor();
+    *show_api &= editor.code.language.has_stubs();
+    api_window(contexts.ctx_mut(), &editor.stubs, &mut show_api);
+    author::window(contexts.ctx_mut(), &mut commands, &mut editor.author, &mut results.level, execution.interactive());
     let physical_position = UVec2::new(left as u32, 0).min(window.physical_size() - UVec2::splat(1));
     let physical_size = (window.physical_size() - physical_position - UVec2::new(0, bottom as u32)).max(UVec2::splat(1));
-    camera.viewport = Some(Viewport { physical_position, physical_size, ..Default::default() });
+    camera.0.viewport = Some(Viewport { physical_position, physical_size, ..Default::default() });
+}
+
+fn controls(
+    ui: &mut egui::Ui,
+    execution: ExecutionState,
+    next_execution: &mut NextState<ExecutionState>,
+    debug: &mut DebugParams,
+    code: &Code,
+    compiles: bool,
+) {
+    let startable = compiles || execution != ExecutionState::Stopped;
+    if execution.can_run() && ui.add_enabled(startable, egui::Button::new("Run")).on_disabled_hover_text("Fix the syntax errors first").clicked() {
+        next_execution.set(ExecutionState::Running);
+        debug.step(StepMode::Action);
+    }
+    if execution.can_pause() && ui.button("Pause").clicked() {
+        next_execution.set(ExecutionState::Stepping);
+        debug.pause();
+    }
+    if execution == ExecutionState::Stopped {
+        if ui.add_enabled(startable, egui::Button::new("Step")).on_hover_text("Run until the next action").clicked() {
+            next_execution.set(ExecutionState::Stepping);
+            debug.step(StepMode::Action);
+        }
+        if ui.add_enabled(startable, egui::Button::new("Step into")).on_hover_text("Pause on the first line").clicked() {
+            next_execution.set(ExecutionState::Stepping);
+            debug.step(StepMode::Into);
+        }
+    } else if execution == ExecutionState::Stepping {
+        let waiting = debug.is_waiting();
+        for (label, hint, mode) in [
+            ("Step", "Run until the next action", StepMode::Action),
+            ("Step into", "Run until the next line, entering function calls", StepMode::Into),
+            ("Step over", "Run until the next line in this function", StepMode::Over),
+            ("Step out", "Run until this function returns", StepMode::Out),
+        ] {
+            if ui.add_enabled(waiting, egui::Button::new(label)).on_hover_text(hint).clicked() {
+                debug.step(mode);
+            }
+        }
+    }
+    let cursor = debug.cursor.filter(|(index, _)| *index < code.files.len());
+    if (execution == ExecutionState::Stopped || execution == ExecutionState::Stepping && debug.is_waiting())
+        && ui.add_enabled(startable && cursor.is_some(), egui::Button::new("Run to cursor")).clicked()
+        && let Some((index, line)) = cursor {
+        next_execution.set(ExecutionState::Running);
+        debug.step(StepMode::Cursor(code.filename(index), line));
+    }
+}
+
+fn console_view(
+    ui: &mut egui::Ui,
+    #[cfg_attr(not(feature = "python"), allow(unused_variables))]
+    commands: &mut Commands,
+    console: &mut ConsoleParams,
+    code: &Code,
+    goto: &mut Option<(usize, usize)>,
+    #[cfg_attr(not(feature = "python"), allow(unused_variables))]
+    execution: ExecutionState,
+) {
+    if let Some(request) = &mut console.input_request && request.0.is_none() {
+        console.console.refocus |= request.is_added();
+        TopBottomPanel::bottom(id!())
+            .show_inside(ui, |ui| prompt(ui, &mut console.console, &mut console.log, request));
+    }
+    #[cfg(feature = "python")]
+    if console.input_request.is_none() && execution == ExecutionState::Finished && let Some(repl) = &console.repl {
+        let ready = console.tasks.is_empty();
+        TopBottomPanel::bottom(id!())
+            .show_inside(ui, |ui| repl_prompt(ui, commands, &mut console.console, &mut console.log, repl, ready));
+    }
+    log_view(ui, &mut console.console, &mut console.log, code, goto);
+}
+
+fn prompt(
+    ui: &mut egui::Ui,
+    console: &mut Console,
+    log: &mut Log,
+    request: &mut InputRequest,
+) {
+    let response = ui.add(egui::TextEdit::singleline(&mut console.input)
+        .font(egui::TextStyle::Monospace)
+        .hint_text("Input")
+        .desired_width(f32::INFINITY));
+    if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+        let line = mem::take(&mut console.input);
+        log.push(Stream::Stdout, &format!("{line}\n"));
+        request.0 = Some(line);
+    } else if mem::take(&mut console.refocus) {
+        response.request_focus();
+    }
+}
+
+#[cfg(feature = "python")]
+fn repl_prompt(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    console: &mut Console,
+    log: &mut Log,
+    repl: &Repl,
+    ready: bool,
+) {
+    ui.horizontal(|ui| {
+        ui.monospace(repl.prompt());
+        let response = ui.add_enabled(ready, egui::TextEdit::singleline(&mut console.input)
+            .font(egui::TextStyle::Monospace)
+            .hint_text("Evaluate Python")
+            .desired_width(f32::INFINITY));
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let line = mem::take(&mut console.input);
+            log.push(Stream::Stdout, &format!("{}{line}\n", repl.prompt()));
+            if !line.trim().is_empty() {
+                console.history.push(line.clone());
+            }
+            console.history_index = console.history.len();
+            console.refocus = true;
+            commands.run_system_cached_with(repl::evaluate, line);
+        } else if response.has_focus() {
+            if ui.input(|input| input.key_pressed(egui::Key::ArrowUp)) && console.history_index > 0 {
+                console.history_index -= 1;
+                console.input.clone_from(&console.history[console.history_index]);
+            } else if ui.input(|input| input.key_pressed(egui::Key::ArrowDown)) && console.history_index < console.history.len() {
+                console.history_index += 1;
+                console.input = console.history.get(console.history_index).cloned().unwrap_or_default();
+            }
+        } else if ready && mem::take(&mut console.refocus) {
+            response.request_focus();
+        }
+    });
+}
+
+fn log_view(
+    ui: &mut egui::Ui,
+    console: &mut Console,
+    log: &mut Log,
+    code: &Code,
+    goto: &mut Option<(usize, usize)>,
+) {
+    ui.horizontal(|ui| {
+        if ui.button("Clear").clicked() {
+            log.0.clear();
+        }
+        ui.add(egui::TextEdit::singleline(&mut console.search)
+            .hint_text("Search")
+            .desired_width(f32::INFINITY));
+    });
+    let search = console.search.to_lowercase();
+    let stdout = ui.visuals().text_color();
+    let stderr = ui.visuals().error_fg_color;
+    Frame::canvas(ui.style())
+        .show(ui, |ui| {
+            ScrollArea::both()
+                .stick_to_bottom(true)
+                .max_width(f32::INFINITY)
+                .auto_shrink(false)
+                .show(ui, |ui| {
+                    ui.spacing_mut().item_spacing.y = 0.;
+                    for entry in &log.0 {
+                        let colour = match entry.stream {
+                            Stream::Stdout => stdout,
+                            Stream::Stderr => stderr,
+                        };
+                        for line in entry.text.lines() {
+                            if !line.to_lowercase().contains(&search) {
+                                continue;
+                            }
+                            let text = RichText::new(line).monospace().color(colour);
+                            if let Some((file, number)) = traceback_line(line) && let Some(index) = code.find(file) {
+                                let label = ui.add(Label::new(text.underline()).extend().sense(Sense::click()));
+                                if label.on_hover_cursor(CursorIcon::PointingHand).clicked() {
+                                    *goto = Some((index, number));
+                                }
+                            } else {
+                                ui.add(Label::new(text).extend());
+                            }
+                        }
+                    }
+                });
+        });
+}
+
+fn results_view(ui: &mut egui::Ui, statistics: &Statistics, par: &Par) {
+    CollapsingHeader::new("Results")
+        .id_salt(id!())
+        .default_open(true)
+        .show(ui, |ui| {
+            Grid::new(id!()).striped(true).show(ui, |ui| {
+                ui.label("");
+                ui.strong("Score");
+                ui.strong("Par");
+                ui.end_row();
+                for (name, value, par) in [
+                    ("Moves", statistics.moves, par.moves),
+                    ("Keys used", statistics.keys, par.keys),
+                    ("Items picked up", statistics.pickups, par.pickups),
+                    ("Ticks", statistics.ticks, par.ticks),
+                    ("Lines of code", statistics.lines, par.lines),
+                ] {
+                    ui.label(name);
+                    ui.label(value.to_string());
+                    match par {
+                        Some(par) if value <= par => ui.label(format!("{par} ✔")),
+                        Some(par) => ui.colored_label(ui.visuals().warn_fg_color, format!("{par} ✘")),
+                        None => ui.weak("–"),
+                    };
+                    ui.end_row();
+                }
+                ui.label("Run time");
+                ui.label(format!("{:.1} s", statistics.run_time.as_secs_f32()));
+                ui.weak("–");
+                ui.end_row();
+            });
+        });
+}
+
+fn tabs(ui: &mut egui::Ui, code: &mut Code, interactive: bool) {
+    ui.horizontal(|ui| {
+        for index in 0..code.files.len() {
+            if index == code.current && let Some(name) = &mut code.renaming {
+                let response = ui.add(egui::TextEdit::singleline(name).desired_width(80.));
+                if response.lost_focus() {
+                    let name = code.renaming.take().unwrap_or_default();
+                    if code.is_valid_name(&name) {
+                        code.rename(index, name);
+                    }
+                } else {
+                    response.request_focus();
+                }
+                continue;
+            }
+            let tab = ui.selectable_label(index == code.current, code.filename(index));
+            if tab.clicked() {
+                code.current = index;
+            }
+            if interactive && index > 0 {
+                tab.context_menu(|ui| {
+                    if ui.button("Rename").clicked() {
+                        code.current = index;
+                        code.renaming = Some(code.files[index].name.clone());
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete…").clicked() {
+                        code.deleting = Some(index);
+                        ui.close_menu();
+                    }
+                });
+            }
+        }
+        if let Some(index) = code.deleting.filter(|index| *index < code.files.len()) {
+            let modal = Modal::new(id!()).show(ui.ctx(), |ui| {
+                ui.label(format!("Delete {}? Its saved copy is deleted too.", code.filename(index)));
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        code.remove(index);
+                        code.deleting = None;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        code.deleting = None;
+                    }
+                });
+            });
+            if modal.should_close() {
+                code.deleting = None;
+            }
+        }
+        if !interactive {
+            return;
+        }
+        if ui.button("+").on_hover_text("New file").clicked() {
+            let name = (1..=code.files.len()).map(|i| format!("module{i}")).find(|name| code.is_valid_name(name)).unwrap_or_default();
+            code.add(name.clone(), String::new());
+            code.renaming = Some(name);
+        }
+        let extension = code.language.extension();
+        if ui.button("Open…").clicked() && let Some(path) = rfd::FileDialog::new()
+            .set_title("Open File")
+            .add_filter(code.language.name(), &[extension])
+            .pick_file() {
+            match fs::read_to_string(&path) {
+                Ok(text) => {
+                    let name = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+                    let exists = code.files.iter().any(|file| file.name == name);
+                    if exists || code.is_valid_name(&name) {
+                        code.add(name, text);
+                    } else {
+                        error!("{name:?} is not a valid module name");
+                    }
+                }
+                Err(e) => error!("Failed to open {}: {e}", path.display()),
+            }
+        }
+        if ui.button("Save…").clicked() && let Some(path) = rfd::FileDialog::new()
+            .set_title("Save File")
+            .add_filter(code.language.name(), &[extension])
+            .set_file_name(code.filename(code.current))
+            .save_file()
+            && let Err(e) = fs::write(&path, &code.current_mut().text) {
+            error!("Failed to save {}: {e}", path.display());
+        }
+    });
+}
+
+fn api_window(ctx: &egui::Context, stubs: &Stubs, open: &mut bool) {
+    egui::Window::new("API")
+        .id(id!())
+        .open(open)
+        .show(ctx, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                code_view_ui(ui, &syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style()), &stubs.source, "py");
+            });
+        });
+}
+
+fn code_editor(
+    ui: &mut egui::Ui,
+    code: &mut String,
+    index: usize,
+    interactive: bool,
+    goto: Option<usize>,
+    highlight: Option<usize>,
+    hits: Option<&HashMap<usize, u32>>,
+    error: Option<&SyntaxError>,
+    completion: &mut Completion,
+    stubs: Option<&Stubs>,
+) -> (bool, Option<usize>) {
+    let id = id!().with(index);
+    let completed = interactive && stubs.is_some_and(|stubs| completion.before(ui, id, code, stubs));
+    let mut output = egui::TextEdit::multiline(code)
+        .id(id)
+        .interactive(interactive)
+        .font(egui::TextStyle::Monospace)
+        .code_editor()
+        .desired_rows(10)
+        .lock_focus(true)
+        .desired_width(f32::INFINITY)
+        .frame(false)
+        .margin(Margin { left: if hits.is_some() { GUTTER_WIDTH as i8 } else { 0 }, ..Margin::same(0) })
+        .layouter(&mut |ui: &egui::Ui, string, _wrap_width| {
+            let mut layout_job = syntax_highlighting::highlight(
+                ui.ctx(),
+                ui.style(),
+                &syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style()),
+                string,
+                // Starlark is a dialect of Python, and there's no highlighting for it
+                "py",
+            );
+            layout_job.wrap.max_width = f32::INFINITY;
+            if let Some(error) = error {
+                underline(&mut layout_job, error_range(string, error), Stroke::new(1.5_f32, ui.visuals().error_fg_color));
+            }
+            ui.fonts(|f| f.layout_job(layout_job))
+        })
+        .show(ui);
+    if let Some(stubs) = stubs {
+        if interactive {
+            completion.after(ui, &output, code, stubs);
+        }
+        hover(&output, code, stubs);
+    }
+    let changed = completed || output.response.changed();
+    let caret = output.cursor_range.map(|range| range.primary.rcursor.row + 1);
+    if let Some(line) = highlight {
+        let span = output.galley.pos_from_ccursor(line_start(code, line)).translate(output.galley_pos.to_vec2());
+        let rect = Rect::from_x_y_ranges(output.response.rect.x_range(), span.y_range());
+        ui.painter().rect_filled(rect, 0., ui.visuals().selection.bg_fill.gamma_multiply(0.4));
+    }
+    if let Some(hits) = hits {
+        heatmap(ui, &output, hits);
+    }
+    if let Some(line) = goto {
+        let ccursor = line_start(code, line);
+        output.state.cursor.set_char_range(Some(CCursorRange::one(ccursor)));
+        output.state.store(ui.ctx(), output.response.id);
+        output.response.request_focus();
+        ui.scroll_to_rect(output.galley.pos_from_ccursor(ccursor).translate(output.galley_pos.to_vec2()), Some(Align::Center));
+    }
+    (changed, caret)
+}
+
+fn heatmap(ui: &egui::Ui, output: &egui::text_edit::TextEditOutput, hits: &HashMap<usize, u32>) {
+    let max = hits.values().copied().max().unwrap_or(1) as f32;
+    let left = output.response.rect.left();
+    let font = egui::TextStyle::Small.resolve(ui.style());
+    for (index, row) in output.galley.rows.iter().enumerate() {
+        let Some(&count) = hits.get(&(index + 1)) else { continue; };
+        let span = row.rect.translate(output.galley_pos.to_vec2());
+        let rect = Rect::from_x_y_ranges(left..=left + GUTTER_WIDTH - 4., span.y_range());
+        let heat = ui.visuals().warn_fg_color.gamma_multiply(0.2 + 0.8 * count as f32 / max);
+        ui.painter().rect_filled(rect, 2., heat);
+        ui.painter().text(rect.right_center(), Align2::RIGHT_CENTER, count.to_string(), font.clone(), ui.visuals().strong_text_color());
+    }
+}
+
+fn syntax_errors(ui: &mut egui::Ui, syntax: &Syntax, code: &Code, goto: &mut Option<(usize, usize)>) {
+    for index in 0..code.files.len() {
+        let Some(error) = syntax.error(index) else { continue; };
+        let text = format!("{}:{}:{}: {}", code.filename(index), error.line, error.column + 1, error.message);
+        let label = ui.add(Label::new(RichText::new(text).monospace().color(ui.visuals().error_fg_color)).sense(Sense::click()));
+        if label.on_hover_cursor(CursorIcon::PointingHand).clicked() {
+            *goto = Some((index, error.line));
+        }
+    }
+}
+
+fn offset(text: &str, line: usize, column: usize) -> usize {
+    let mut start = 0;
+    for (index, text_line) in text.split_inclusive('\n').enumerate() {
+        if index + 1 == line {
+            return start + text_line.char_indices().nth(column).map_or(text_line.trim_end_matches('\n').len(), |(offset, _)| offset);
+        }
+        start += text_line.len();
+    }
+    text.len()
+}
+
+pub(super) fn error_range(text: &str, error: &SyntaxError) -> Range<usize> {
+    let start = offset(text, error.line, error.column);
+    let end = offset(text, error.end_line, error.end_column).max(start);
+    let end = start + text[start..end].trim_end_matches('\n').len();
+    if start < end {
+        return start..end;
+    }
+    match (text[..start].chars().next_back(), text[start..].chars().next()) {
+        (_, Some(next)) if next != '\n' => start..start + next.len_utf8(),
+        (Some(previous), _) if previous != '\n' => start - previous.len_utf8()..start,
+        _ => start..start,
+    }
+}
+
+pub(super) fn underline(job: &mut LayoutJob, range: Range<usize>, stroke: Stroke) {
+    let mut sections = Vec::with_capacity(job.sections.len() + 2);
+    for section in job.sections.drain(..) {
+        let bytes = section.byte_range.clone();
+        let start = range.start.clamp(bytes.start, bytes.end);
+        let end = range.end.clamp(bytes.start, bytes.end);
+        for (part, underlined) in [(bytes.start..start, false), (start..end, true), (end..bytes.end, false)] {
+            if part.is_empty() {
+                continue;
+            }
+            let mut split = section.clone();
+            if part.start != bytes.start {
+                split.leading_space = 0.;
+            }
+            split.byte_range = part;
+            if underlined {
+                split.format.underline = stroke;
+            }
+            sections.push(split);
+        }
+    }
+    job.sections = sections;
+}
+
+fn line_start(code: &str, line: usize) -> CCursor {
+    CCursor::new(code.lines().take(line.saturating_sub(1)).map(|line| line.chars().count() + 1).sum())
 }
@@ -1,9 +1,9 @@
 #![allow(clippy::module_inception)]
 
-use std::fs;
 use bevy::app::AppExit;
 use bevy::color::{Color, Srgba};
 use bevy::hierarchy::{BuildChildren, ChildBuild};
+use bevy::log::error;
 use bevy::prelude::{Camera2d, Commands, EventWriter, NextState, ResMut, StateScoped};
 use bevy::ui::{AlignItems, BackgroundColor, BoxShadow, FlexDirection, IsDefaultUiCamera, JustifyContent, Node, Val};
 use crate::game::level;
@@ -15,7 +15,7 @@ use crate::ui::ChildBuilderExt;
 pub(super) fn build(mut commands: Commands) {
     commands.spawn((Camera2d, IsDefaultUiCamera, StateScoped(Scene::MainMenu)));
     let play = commands.register_system(|mut next_state: ResMut<NextState<Scene>>, mut current_level: ResMut<CurrentLevel>| {
-        *current_level = CurrentLevel(level::parse(include_str!("../../../levels/test.plvl")));
+        *current_level = CurrentLevel(level::parse("test", include_str!("../../../levels/test.plvl")).unwrap());
         next_state.set(Scene::Editor);
     });
     let load = commands.register_system(|mut next_state: ResMut<NextState<Scene>>, mut current_level: ResMut<CurrentLevel>| {
@@ -23,7 +23,13 @@ pub(super) fn build(mut commands: Commands) {
             .set_title("Load Level")
             .add_filter("Pythoneer Level", &["plvl"])
             .pick_file() else { return; };
-        let level = level::parse(&fs::read_to_string(file).unwrap());
+        let level = match level::load(&file) {
+            Ok(level) => level,
+            Err(e) => {
+                error!("Failed to load {}: {e}", file.display());
+                return;
+            }
+        };
         *current_level = CurrentLevel(level);
         next_state.set(Scene::Editor);
     });